@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS page_archive;
+DROP TABLE IF EXISTS page_blob;
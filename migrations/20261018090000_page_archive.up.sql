@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE page_blob(
+	hash TEXT PRIMARY KEY,
+	body BYTEA NOT NULL
+);
+
+CREATE TABLE page_archive(
+	id SERIAL PRIMARY KEY,
+	url TEXT NOT NULL,
+	hash TEXT NOT NULL REFERENCES page_blob(hash),
+	fetched_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX page_archive_url_fetched_at_idx ON page_archive(url, fetched_at DESC);
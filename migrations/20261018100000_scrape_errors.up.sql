@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE scrape_errors(
+	id SERIAL PRIMARY KEY,
+	game TEXT NOT NULL,
+	url TEXT NOT NULL,
+	card_identifier TEXT,
+	kind TEXT NOT NULL,
+	message TEXT NOT NULL,
+	snippet TEXT,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	resolved_at TIMESTAMPTZ
+);
+
+CREATE INDEX scrape_errors_unresolved_idx ON scrape_errors(game) WHERE resolved_at IS NULL;
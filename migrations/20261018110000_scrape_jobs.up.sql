@@ -0,0 +1,20 @@
+-- Add up migration script here
+CREATE TABLE scrape_jobs(
+	id BIGSERIAL PRIMARY KEY,
+	queue TEXT NOT NULL,
+	payload JSONB NOT NULL,
+	-- pending, running, done, dead
+	status TEXT NOT NULL DEFAULT 'pending',
+	attempts INT NOT NULL DEFAULT 0,
+	max_attempts INT NOT NULL DEFAULT 5,
+	-- visibility timeout while running, retry backoff while pending
+	locked_until TIMESTAMPTZ,
+	last_error TEXT,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- a finished job can be seeded again
+CREATE UNIQUE INDEX scrape_jobs_unfinished_key ON scrape_jobs(queue, payload) WHERE status <> 'done';
+
+CREATE INDEX scrape_jobs_claim_idx ON scrape_jobs(queue, status, locked_until);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+CREATE TABLE ws_progress(
+	id SERIAL PRIMARY KEY,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	current_page INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO ws_progress(current_page)
+SELECT COALESCE((SELECT cursor::INTEGER + 1 FROM scrape_checkpoints WHERE job = 'ws_scrape'), 0);
+
+DROP TABLE IF EXISTS scrape_checkpoints;
@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE scrape_checkpoints(
+	job TEXT PRIMARY KEY,
+	-- last item the job finished
+	cursor TEXT NOT NULL,
+	updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- ws_progress stored the page after the last finished one
+INSERT INTO scrape_checkpoints(job, cursor)
+SELECT 'ws_scrape', (current_page - 1)::TEXT FROM ws_progress WHERE current_page > 1;
+
+DROP TABLE ws_progress;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+CREATE TABLE scrape_runs(
+	id SERIAL PRIMARY KEY,
+	game TEXT NOT NULL,
+	command TEXT NOT NULL,
+	-- running, succeeded, failed
+	status TEXT NOT NULL DEFAULT 'running',
+	started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	finished_at TIMESTAMPTZ,
+	pages_fetched INTEGER NOT NULL DEFAULT 0,
+	cards_inserted INTEGER NOT NULL DEFAULT 0,
+	cards_updated INTEGER NOT NULL DEFAULT 0,
+	cards_unchanged INTEGER NOT NULL DEFAULT 0,
+	error_count INTEGER NOT NULL DEFAULT 0,
+	error_message TEXT
+);
+
+CREATE INDEX scrape_runs_game_idx ON scrape_runs(game, started_at DESC);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE yugioh_printing_detail DROP COLUMN IF EXISTS last_seen_run;
+ALTER TABLE pokemon_trainer_printing DROP COLUMN IF EXISTS last_seen_run;
+DROP TABLE IF EXISTS card_changes;
@@ -0,0 +1,19 @@
+-- Add up migration script here
+CREATE TABLE card_changes(
+	id BIGSERIAL PRIMARY KEY,
+	run_id INTEGER REFERENCES scrape_runs(id) ON DELETE SET NULL,
+	game TEXT NOT NULL,
+	card_key TEXT NOT NULL,
+	-- new, changed, removed
+	change TEXT NOT NULL,
+	field TEXT,
+	old_value TEXT,
+	new_value TEXT,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX card_changes_run_idx ON card_changes(run_id);
+
+-- the last run that saw the printing, to detect printings that disappeared
+ALTER TABLE pokemon_trainer_printing ADD COLUMN last_seen_run INTEGER;
+ALTER TABLE yugioh_printing_detail ADD COLUMN last_seen_run INTEGER;
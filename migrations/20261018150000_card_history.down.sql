@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP TRIGGER IF EXISTS tcg_collector_history ON tcg_collector;
+DROP TRIGGER IF EXISTS ws_cards_history ON ws_cards;
+DROP TRIGGER IF EXISTS one_piece_history ON one_piece;
+DROP TRIGGER IF EXISTS yugioh_printing_detail_history ON yugioh_printing_detail;
+DROP TRIGGER IF EXISTS pokemon_trainer_printing_history ON pokemon_trainer_printing;
+DROP FUNCTION IF EXISTS record_card_history;
+DROP FUNCTION IF EXISTS card_history_key;
+DROP TABLE IF EXISTS card_history;
+ALTER TABLE tcg_collector DROP COLUMN IF EXISTS last_seen_run;
+ALTER TABLE ws_cards DROP COLUMN IF EXISTS last_seen_run;
+ALTER TABLE one_piece DROP COLUMN IF EXISTS last_seen_run;
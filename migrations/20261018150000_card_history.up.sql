@@ -0,0 +1,71 @@
+-- Add up migration script here
+ALTER TABLE one_piece ADD COLUMN last_seen_run INTEGER;
+ALTER TABLE ws_cards ADD COLUMN last_seen_run INTEGER;
+ALTER TABLE tcg_collector ADD COLUMN last_seen_run INTEGER;
+
+-- every version of a row of the core card tables; valid_to is NULL for the current one
+CREATE TABLE card_history(
+	id BIGSERIAL PRIMARY KEY,
+	table_name TEXT NOT NULL,
+	card_key TEXT NOT NULL,
+	data JSONB NOT NULL,
+	valid_from TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	valid_to TIMESTAMPTZ,
+	run_id INTEGER REFERENCES scrape_runs(id) ON DELETE SET NULL
+);
+
+CREATE INDEX card_history_key_idx ON card_history(table_name, card_key, valid_from);
+CREATE INDEX card_history_current_idx ON card_history(table_name, card_key) WHERE valid_to IS NULL;
+
+CREATE FUNCTION card_history_key(data JSONB, columns TEXT[]) RETURNS TEXT AS $$
+	SELECT string_agg(data ->> c, '|' ORDER BY n) FROM unnest(columns) WITH ORDINALITY AS t(c, n)
+$$ LANGUAGE sql IMMUTABLE;
+
+-- trigger arguments are the key columns of the table
+CREATE FUNCTION record_card_history() RETURNS TRIGGER AS $$
+BEGIN
+	IF TG_OP = 'UPDATE' AND (to_jsonb(NEW) - 'last_seen_run') = (to_jsonb(OLD) - 'last_seen_run') THEN
+		RETURN NEW;
+	END IF;
+	IF TG_OP <> 'INSERT' THEN
+		UPDATE card_history SET valid_to = NOW()
+		WHERE table_name = TG_TABLE_NAME
+			AND card_key = card_history_key(to_jsonb(OLD), TG_ARGV)
+			AND valid_to IS NULL;
+	END IF;
+	IF TG_OP = 'DELETE' THEN
+		RETURN OLD;
+	END IF;
+	INSERT INTO card_history(table_name, card_key, data, run_id)
+	VALUES(TG_TABLE_NAME, card_history_key(to_jsonb(NEW), TG_ARGV), to_jsonb(NEW) - 'last_seen_run', NEW.last_seen_run);
+	RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+INSERT INTO card_history(table_name, card_key, data)
+SELECT 'pokemon_trainer_printing', code, to_jsonb(t) - 'last_seen_run' FROM pokemon_trainer_printing t;
+INSERT INTO card_history(table_name, card_key, data)
+SELECT 'yugioh_printing_detail', card_id || '|' || expansion_name || '|' || rarity, to_jsonb(t) - 'last_seen_run'
+FROM yugioh_printing_detail t;
+INSERT INTO card_history(table_name, card_key, data)
+SELECT 'one_piece', code, to_jsonb(t) - 'last_seen_run' FROM one_piece t;
+INSERT INTO card_history(table_name, card_key, data)
+SELECT 'ws_cards', code, to_jsonb(t) - 'last_seen_run' FROM ws_cards t;
+INSERT INTO card_history(table_name, card_key, data)
+SELECT 'tcg_collector', url, to_jsonb(t) - 'last_seen_run' FROM tcg_collector t;
+
+CREATE TRIGGER pokemon_trainer_printing_history
+AFTER INSERT OR UPDATE OR DELETE ON pokemon_trainer_printing
+FOR EACH ROW EXECUTE FUNCTION record_card_history('code');
+CREATE TRIGGER yugioh_printing_detail_history
+AFTER INSERT OR UPDATE OR DELETE ON yugioh_printing_detail
+FOR EACH ROW EXECUTE FUNCTION record_card_history('card_id', 'expansion_name', 'rarity');
+CREATE TRIGGER one_piece_history
+AFTER INSERT OR UPDATE OR DELETE ON one_piece
+FOR EACH ROW EXECUTE FUNCTION record_card_history('code');
+CREATE TRIGGER ws_cards_history
+AFTER INSERT OR UPDATE OR DELETE ON ws_cards
+FOR EACH ROW EXECUTE FUNCTION record_card_history('code');
+CREATE TRIGGER tcg_collector_history
+AFTER INSERT OR UPDATE OR DELETE ON tcg_collector
+FOR EACH ROW EXECUTE FUNCTION record_card_history('url');
@@ -0,0 +1,22 @@
+-- Add up migration script here
+CREATE TABLE yugioh_card(
+	card_id TEXT PRIMARY KEY,
+	name_jp TEXT NOT NULL,
+	name_en TEXT NOT NULL,
+	-- monster attribute, e.g. 闇; NULL for spells and traps
+	attribute TEXT,
+	level INTEGER,
+	rank INTEGER,
+	link_rating INTEGER,
+	-- monster types, or the spell/trap icon, e.g. {ドラゴン族,効果}
+	types TEXT[] NOT NULL DEFAULT '{}',
+	-- TEXT since a card can have ? as ATK/DEF
+	atk TEXT,
+	def TEXT,
+	pendulum_scale INTEGER,
+	pendulum_effect TEXT,
+	-- top-left, top, top-right, left, right, bottom-left, bottom, bottom-right
+	link_arrows TEXT[] NOT NULL DEFAULT '{}',
+	effect_text TEXT,
+	updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,27 @@
+-- Add down migration script here
+DROP TRIGGER yugioh_printing_detail_history ON yugioh_printing_detail;
+CREATE TRIGGER yugioh_printing_detail_history
+AFTER INSERT OR UPDATE OR DELETE ON yugioh_printing_detail
+FOR EACH ROW EXECUTE FUNCTION record_card_history('card_id', 'expansion_name', 'rarity');
+
+DELETE FROM card_changes WHERE game = 'yugioh' AND card_key NOT LIKE '%|ja|%';
+UPDATE card_changes SET card_key = regexp_replace(card_key, '^([^|]*)\|ja\|', '\1|')
+WHERE game = 'yugioh';
+DELETE FROM card_history WHERE table_name = 'yugioh_printing_detail' AND data ->> 'locale' <> 'ja';
+UPDATE card_history
+SET data = data - 'locale' - 'name',
+	card_key = regexp_replace(card_key, '^([^|]*)\|ja\|', '\1|')
+WHERE table_name = 'yugioh_printing_detail';
+
+DELETE FROM yugioh_printing_detail WHERE locale <> 'ja';
+ALTER TABLE yugioh_printing_detail DROP CONSTRAINT yugioh_printing_detail_card_id_locale_expansion_name_rarity_key;
+ALTER TABLE yugioh_printing_detail ADD UNIQUE(card_id, expansion_name, rarity);
+ALTER TABLE yugioh_printing_detail DROP COLUMN name;
+ALTER TABLE yugioh_printing_detail DROP COLUMN locale;
+
+DELETE FROM yugioh_printing_link WHERE locale <> 'ja';
+ALTER TABLE yugioh_printing_link DROP COLUMN locale;
+ALTER TABLE yugioh_printing_link ADD UNIQUE(url);
+DELETE FROM yugioh_expansion_link WHERE locale <> 'ja';
+ALTER TABLE yugioh_expansion_link DROP COLUMN locale;
+ALTER TABLE yugioh_expansion_link ADD UNIQUE(url);
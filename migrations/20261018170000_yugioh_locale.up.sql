@@ -0,0 +1,27 @@
+-- Add up migration script here
+-- request_locale of the Konami database a row was read from
+ALTER TABLE yugioh_expansion_link ADD COLUMN locale TEXT NOT NULL DEFAULT 'ja';
+ALTER TABLE yugioh_expansion_link DROP CONSTRAINT yugioh_expansion_link_url_key;
+ALTER TABLE yugioh_expansion_link ADD UNIQUE(url, locale);
+ALTER TABLE yugioh_printing_link ADD COLUMN locale TEXT NOT NULL DEFAULT 'ja';
+ALTER TABLE yugioh_printing_link DROP CONSTRAINT yugioh_printing_link_url_key;
+ALTER TABLE yugioh_printing_link ADD UNIQUE(url, locale);
+
+-- name_jp and name_en are only read from the ja database, other locales get them from yugioh_card
+ALTER TABLE yugioh_printing_detail ADD COLUMN locale TEXT NOT NULL DEFAULT 'ja';
+ALTER TABLE yugioh_printing_detail ADD COLUMN name TEXT NOT NULL DEFAULT '';
+UPDATE yugioh_printing_detail SET name = name_jp;
+ALTER TABLE yugioh_printing_detail DROP CONSTRAINT yugioh_printing_detail_card_id_expansion_name_rarity_key;
+ALTER TABLE yugioh_printing_detail ADD UNIQUE(card_id, locale, expansion_name, rarity);
+
+UPDATE card_history
+SET data = data || '{"locale": "ja"}' || jsonb_build_object('name', data ->> 'name_jp'),
+	card_key = regexp_replace(card_key, '^([^|]*)\|', '\1|ja|')
+WHERE table_name = 'yugioh_printing_detail';
+UPDATE card_changes SET card_key = regexp_replace(card_key, '^([^|]*)\|', '\1|ja|')
+WHERE game = 'yugioh';
+
+DROP TRIGGER yugioh_printing_detail_history ON yugioh_printing_detail;
+CREATE TRIGGER yugioh_printing_detail_history
+AFTER INSERT OR UPDATE OR DELETE ON yugioh_printing_detail
+FOR EACH ROW EXECUTE FUNCTION record_card_history('card_id', 'locale', 'expansion_name', 'rarity');
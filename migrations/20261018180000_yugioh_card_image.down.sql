@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE yugioh_card DROP COLUMN image;
+ALTER TABLE yugioh_card DROP COLUMN img_src;
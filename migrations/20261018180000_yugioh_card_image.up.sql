@@ -0,0 +1,4 @@
+-- Add up migration script here
+-- img_src is the get_image.action url on the Konami database, image where the downloaded copy is stored
+ALTER TABLE yugioh_card ADD COLUMN img_src TEXT;
+ALTER TABLE yugioh_card ADD COLUMN image TEXT;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+UPDATE card_history SET data = data || '{"number": "NONE-000", "expansion_code": "NONE"}'
+WHERE table_name = 'yugioh_printing_detail' AND data -> 'number' = 'null';
+UPDATE yugioh_printing_detail SET number = 'NONE-000', expansion_code = 'NONE' WHERE number IS NULL;
+ALTER TABLE yugioh_printing_detail ALTER COLUMN number SET NOT NULL;
+ALTER TABLE yugioh_printing_detail ALTER COLUMN expansion_code SET NOT NULL;
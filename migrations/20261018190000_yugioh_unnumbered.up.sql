@@ -0,0 +1,7 @@
+-- Add up migration script here
+-- printings without a card number used to be stored as NONE-000
+ALTER TABLE yugioh_printing_detail ALTER COLUMN number DROP NOT NULL;
+ALTER TABLE yugioh_printing_detail ALTER COLUMN expansion_code DROP NOT NULL;
+UPDATE yugioh_printing_detail SET number = NULL, expansion_code = NULL WHERE number = 'NONE-000';
+UPDATE card_history SET data = data || '{"number": null, "expansion_code": null}'
+WHERE table_name = 'yugioh_printing_detail' AND data ->> 'number' = 'NONE-000';
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP VIEW IF EXISTS yugioh_current_legality;
+DROP TABLE IF EXISTS yugioh_forbidden_limited;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+-- one row per card on a published OCG Forbidden/Limited list; cards not on the list are unlimited
+CREATE TABLE yugioh_forbidden_limited(
+	effective_date DATE NOT NULL,
+	card_id TEXT NOT NULL,
+	-- forbidden, limited, semi_limited
+	status TEXT NOT NULL,
+	PRIMARY KEY(effective_date, card_id)
+);
+
+CREATE VIEW yugioh_current_legality AS
+SELECT card_id, status, effective_date
+FROM yugioh_forbidden_limited
+WHERE effective_date = (
+	SELECT MAX(effective_date) FROM yugioh_forbidden_limited WHERE effective_date <= CURRENT_DATE
+);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+ALTER TABLE pokemon_trainer_printing
+	DROP COLUMN evolve_marker,
+	DROP COLUMN img_src,
+	DROP COLUMN hp,
+	DROP COLUMN weak_point,
+	DROP COLUMN resist,
+	DROP COLUMN escape,
+	DROP COLUMN expansion_symbol,
+	DROP COLUMN energy,
+	DROP COLUMN artist;
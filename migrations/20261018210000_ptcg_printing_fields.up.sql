@@ -0,0 +1,12 @@
+-- Add up migration script here
+-- everything else the asia.pokemon-card.com detail page has
+ALTER TABLE pokemon_trainer_printing
+	ADD COLUMN evolve_marker TEXT,
+	ADD COLUMN img_src TEXT,
+	ADD COLUMN hp TEXT,
+	ADD COLUMN weak_point TEXT,
+	ADD COLUMN resist TEXT,
+	ADD COLUMN escape TEXT,
+	ADD COLUMN expansion_symbol TEXT,
+	ADD COLUMN energy TEXT,
+	ADD COLUMN artist TEXT;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE pokemon_trainer_skill_cost;
+DROP TABLE pokemon_trainer_skill;
@@ -0,0 +1,19 @@
+-- Add up migration script here
+-- card text of a printing, one row per attack, ability, effect or rule box
+CREATE TABLE pokemon_trainer_skill(
+	id SERIAL PRIMARY KEY,
+	code TEXT NOT NULL REFERENCES pokemon_trainer_printing(code) ON UPDATE CASCADE ON DELETE CASCADE,
+	position INTEGER NOT NULL,
+	kind TEXT NOT NULL,
+	name TEXT,
+	damage TEXT,
+	effect TEXT,
+	UNIQUE(code, position)
+);
+
+CREATE TABLE pokemon_trainer_skill_cost(
+	skill_id INTEGER NOT NULL REFERENCES pokemon_trainer_skill(id) ON DELETE CASCADE,
+	position INTEGER NOT NULL,
+	energy TEXT NOT NULL,
+	PRIMARY KEY(skill_id, position)
+);
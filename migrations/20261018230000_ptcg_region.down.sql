@@ -0,0 +1,35 @@
+-- Add down migration script here
+DROP TRIGGER pokemon_trainer_printing_history ON pokemon_trainer_printing;
+CREATE TRIGGER pokemon_trainer_printing_history
+AFTER INSERT OR UPDATE OR DELETE ON pokemon_trainer_printing
+FOR EACH ROW EXECUTE FUNCTION record_card_history('code');
+
+DELETE FROM card_changes WHERE game = 'ptcg' AND card_key NOT LIKE '%|tw';
+UPDATE card_changes SET card_key = regexp_replace(card_key, '\|tw$', '') WHERE game = 'ptcg';
+DELETE FROM card_history WHERE table_name = 'pokemon_trainer_printing' AND data ->> 'region' <> 'tw';
+UPDATE card_history SET data = data - 'region', card_key = regexp_replace(card_key, '\|tw$', '')
+WHERE table_name = 'pokemon_trainer_printing';
+
+ALTER TABLE pokemon_trainer_skill DROP CONSTRAINT pokemon_trainer_skill_code_region_fkey;
+DELETE FROM pokemon_trainer_printing WHERE region <> 'tw';
+ALTER TABLE pokemon_trainer_printing DROP CONSTRAINT expansion_code_number_key;
+ALTER TABLE pokemon_trainer_printing ADD CONSTRAINT expansion_code_number_key UNIQUE(name, number, expansion_code);
+ALTER TABLE pokemon_trainer_printing DROP CONSTRAINT pokemon_trainer_printing_code_region_key;
+ALTER TABLE pokemon_trainer_printing DROP COLUMN region;
+ALTER TABLE pokemon_trainer_printing ADD UNIQUE(code);
+
+DELETE FROM pokemon_trainer_skill WHERE region <> 'tw';
+ALTER TABLE pokemon_trainer_skill DROP CONSTRAINT pokemon_trainer_skill_code_region_position_key;
+ALTER TABLE pokemon_trainer_skill DROP COLUMN region;
+ALTER TABLE pokemon_trainer_skill ADD UNIQUE(code, position);
+ALTER TABLE pokemon_trainer_skill ADD FOREIGN KEY(code)
+	REFERENCES pokemon_trainer_printing(code) ON UPDATE CASCADE ON DELETE CASCADE;
+
+DELETE FROM pokemon_trainer_fetchable_card WHERE region <> 'tw';
+ALTER TABLE pokemon_trainer_fetchable_card DROP CONSTRAINT pokemon_trainer_fetchable_card_code_region_key;
+ALTER TABLE pokemon_trainer_fetchable_card DROP COLUMN region;
+ALTER TABLE pokemon_trainer_fetchable_card ADD UNIQUE(code);
+DELETE FROM pokemon_trainer_expansion WHERE region <> 'tw';
+ALTER TABLE pokemon_trainer_expansion DROP CONSTRAINT pokemon_trainer_expansion_code_region_key;
+ALTER TABLE pokemon_trainer_expansion DROP COLUMN region;
+ALTER TABLE pokemon_trainer_expansion ADD UNIQUE(code);
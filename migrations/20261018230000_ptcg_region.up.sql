@@ -0,0 +1,31 @@
+-- Add up migration script here
+-- asia.pokemon-card.com catalogue a row was read from, card codes are only unique within one
+ALTER TABLE pokemon_trainer_expansion ADD COLUMN region TEXT NOT NULL DEFAULT 'tw';
+ALTER TABLE pokemon_trainer_expansion DROP CONSTRAINT pokemon_trainer_expansion_code_key;
+ALTER TABLE pokemon_trainer_expansion ADD UNIQUE(code, region);
+ALTER TABLE pokemon_trainer_fetchable_card ADD COLUMN region TEXT NOT NULL DEFAULT 'tw';
+ALTER TABLE pokemon_trainer_fetchable_card DROP CONSTRAINT pokemon_trainer_fetchable_card_code_key;
+ALTER TABLE pokemon_trainer_fetchable_card ADD UNIQUE(code, region);
+
+ALTER TABLE pokemon_trainer_skill DROP CONSTRAINT pokemon_trainer_skill_code_fkey;
+ALTER TABLE pokemon_trainer_skill DROP CONSTRAINT pokemon_trainer_skill_code_position_key;
+ALTER TABLE pokemon_trainer_skill ADD COLUMN region TEXT NOT NULL DEFAULT 'tw';
+ALTER TABLE pokemon_trainer_skill ADD UNIQUE(code, region, position);
+
+ALTER TABLE pokemon_trainer_printing ADD COLUMN region TEXT NOT NULL DEFAULT 'tw';
+ALTER TABLE pokemon_trainer_printing DROP CONSTRAINT pokemon_trainer_printing_code_key;
+ALTER TABLE pokemon_trainer_printing ADD UNIQUE(code, region);
+ALTER TABLE pokemon_trainer_printing DROP CONSTRAINT expansion_code_number_key;
+ALTER TABLE pokemon_trainer_printing ADD CONSTRAINT expansion_code_number_key UNIQUE(name, number, expansion_code, region);
+
+ALTER TABLE pokemon_trainer_skill ADD FOREIGN KEY(code, region)
+	REFERENCES pokemon_trainer_printing(code, region) ON UPDATE CASCADE ON DELETE CASCADE;
+
+UPDATE card_history SET data = data || '{"region": "tw"}', card_key = card_key || '|tw'
+WHERE table_name = 'pokemon_trainer_printing';
+UPDATE card_changes SET card_key = card_key || '|tw' WHERE game = 'ptcg';
+
+DROP TRIGGER pokemon_trainer_printing_history ON pokemon_trainer_printing;
+CREATE TRIGGER pokemon_trainer_printing_history
+AFTER INSERT OR UPDATE OR DELETE ON pokemon_trainer_printing
+FOR EACH ROW EXECUTE FUNCTION record_card_history('code', 'region');
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE ptcg_rotation;
+ALTER TABLE pokemon_trainer_printing DROP COLUMN regulation_mark;
@@ -0,0 +1,17 @@
+-- Add up migration script here
+-- letter printed next to the collector number, e.g. G
+ALTER TABLE pokemon_trainer_printing ADD COLUMN regulation_mark TEXT;
+
+-- from effective_date on, marks from oldest_mark up are legal in format;
+-- add a row when a rotation is announced
+CREATE TABLE ptcg_rotation(
+	format TEXT NOT NULL,
+	effective_date DATE NOT NULL,
+	oldest_mark TEXT NOT NULL,
+	PRIMARY KEY(format, effective_date)
+);
+
+INSERT INTO ptcg_rotation(format, effective_date, oldest_mark) VALUES
+	('standard', '2024-01-26', 'E'),
+	('standard', '2025-01-24', 'F'),
+	('expanded', '2024-01-26', 'A');
@@ -0,0 +1,4 @@
+-- Add down migration script here
+ALTER TABLE pokemon_trainer_printing DROP COLUMN strategy_id;
+ALTER TABLE pokewiki DROP COLUMN strategy_id;
+DROP TABLE ptcg_strategy;
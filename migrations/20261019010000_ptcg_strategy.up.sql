@@ -0,0 +1,18 @@
+-- Add up migration script here
+-- every import of a changed strategy adds a version; apply runs the latest one
+CREATE TABLE ptcg_strategy(
+	id SERIAL PRIMARY KEY,
+	exp_code TEXT NOT NULL,
+	region TEXT NOT NULL DEFAULT 'tw',
+	version INTEGER NOT NULL,
+	name TEXT NOT NULL,
+	sources JSONB NOT NULL,
+	author TEXT,
+	created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+	applied_at TIMESTAMPTZ,
+	UNIQUE(exp_code, region, version)
+);
+
+-- the strategy version that last wrote the row, NULL for plain scrapes
+ALTER TABLE pokewiki ADD COLUMN strategy_id INTEGER REFERENCES ptcg_strategy(id) ON DELETE SET NULL;
+ALTER TABLE pokemon_trainer_printing ADD COLUMN strategy_id INTEGER REFERENCES ptcg_strategy(id) ON DELETE SET NULL;
@@ -0,0 +1,10 @@
+CREATE TABLE yugioh_expansion_link(
+    url TEXT NOT NULL,
+    locale TEXT NOT NULL DEFAULT 'ja',
+    UNIQUE(url, locale)
+);
+CREATE TABLE yugioh_printing_link(
+    url TEXT NOT NULL,
+    locale TEXT NOT NULL DEFAULT 'ja',
+    UNIQUE(url, locale)
+);
@@ -0,0 +1,3 @@
+-- the yugioh pipeline walks expansions and printings directly, nothing queues links anymore
+DROP TABLE yugioh_expansion_link;
+DROP TABLE yugioh_printing_link;
@@ -0,0 +1 @@
+ALTER TABLE scrape_errors DROP COLUMN run_id;
@@ -0,0 +1,3 @@
+-- runs of different commands overlap, a run only counts the errors it recorded itself
+ALTER TABLE scrape_errors ADD COLUMN run_id INTEGER REFERENCES scrape_runs(id) ON DELETE SET NULL;
+CREATE INDEX scrape_errors_run_idx ON scrape_errors(run_id);
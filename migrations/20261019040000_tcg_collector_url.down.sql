@@ -0,0 +1,21 @@
+CREATE OR REPLACE FUNCTION record_card_history() RETURNS TRIGGER AS $$
+BEGIN
+	IF TG_OP = 'UPDATE' AND (to_jsonb(NEW) - 'last_seen_run') = (to_jsonb(OLD) - 'last_seen_run') THEN
+		RETURN NEW;
+	END IF;
+	IF TG_OP <> 'INSERT' THEN
+		UPDATE card_history SET valid_to = NOW()
+		WHERE table_name = TG_TABLE_NAME
+			AND card_key = card_history_key(to_jsonb(OLD), TG_ARGV)
+			AND valid_to IS NULL;
+	END IF;
+	IF TG_OP = 'DELETE' THEN
+		RETURN OLD;
+	END IF;
+	INSERT INTO card_history(table_name, card_key, data, run_id)
+	VALUES(TG_TABLE_NAME, card_history_key(to_jsonb(NEW), TG_ARGV), to_jsonb(NEW) - 'last_seen_run', NEW.last_seen_run);
+	RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+ALTER TABLE tcg_collector DROP CONSTRAINT tcg_collector_url_key;
@@ -0,0 +1,35 @@
+-- every refetch inserted another copy of a page, keep the latest one per url
+ALTER TABLE tcg_collector DISABLE TRIGGER tcg_collector_history;
+DELETE FROM tcg_collector a USING tcg_collector b WHERE a.url = b.url AND a.ctid < b.ctid;
+ALTER TABLE tcg_collector ENABLE TRIGGER tcg_collector_history;
+ALTER TABLE tcg_collector ADD CONSTRAINT tcg_collector_url_key UNIQUE(url);
+
+-- the copies each opened a version, the newest one stays open
+UPDATE card_history h SET valid_to = latest.valid_from
+FROM (
+	SELECT DISTINCT ON (card_key) card_key, id, valid_from FROM card_history
+	WHERE table_name = 'tcg_collector' AND valid_to IS NULL
+	ORDER BY card_key, id DESC
+) latest
+WHERE h.table_name = 'tcg_collector' AND h.card_key = latest.card_key
+	AND h.valid_to IS NULL AND h.id < latest.id;
+
+-- an INSERT also closes an open version of its key, in case a table inserts a card twice
+CREATE OR REPLACE FUNCTION record_card_history() RETURNS TRIGGER AS $$
+BEGIN
+	IF TG_OP = 'UPDATE' AND (to_jsonb(NEW) - 'last_seen_run') = (to_jsonb(OLD) - 'last_seen_run') THEN
+		RETURN NEW;
+	END IF;
+	UPDATE card_history SET valid_to = NOW()
+	WHERE table_name = TG_TABLE_NAME
+		AND card_key = card_history_key(
+			CASE WHEN TG_OP = 'INSERT' THEN to_jsonb(NEW) ELSE to_jsonb(OLD) END, TG_ARGV)
+		AND valid_to IS NULL;
+	IF TG_OP = 'DELETE' THEN
+		RETURN OLD;
+	END IF;
+	INSERT INTO card_history(table_name, card_key, data, run_id)
+	VALUES(TG_TABLE_NAME, card_history_key(to_jsonb(NEW), TG_ARGV), to_jsonb(NEW) - 'last_seen_run', NEW.last_seen_run);
+	RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
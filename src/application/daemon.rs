@@ -0,0 +1,108 @@
+use chrono::Utc;
+use tokio::{signal, sync::watch, time::sleep};
+use tracing::{error, info, warn};
+
+use super::Application;
+use crate::{
+    error::Error,
+    schedule::{ScheduleEntry, Task},
+};
+
+impl Application {
+    fn for_new_run(&self) -> Self {
+        Self {
+            repository: self.repository.for_new_run(),
+            client: self.client.for_new_run(),
+            sessions: self.sessions.clone(),
+        }
+    }
+    // runs every entry on its schedule until SIGTERM or ctrl-c
+    pub async fn daemon(&self, schedule: &[ScheduleEntry]) {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let loops = futures::future::join_all(
+            schedule
+                .iter()
+                .map(|entry| self.schedule_loop(entry, shutdown_rx.clone())),
+        );
+        let signals = async {
+            wait_for_shutdown().await;
+            info!("shutting down, interrupting running jobs");
+            let _ = shutdown_tx.send(true);
+        };
+        tokio::join!(loops, signals);
+    }
+    async fn schedule_loop(&self, entry: &ScheduleEntry, mut shutdown: watch::Receiver<bool>) {
+        let command = entry.command();
+        // recomputed after each run, so a run that overran its next slot is not replayed
+        while let Some(next) = entry.cron.upcoming(Utc).next() {
+            info!("{command} scheduled at {next}");
+            let wait = (next - Utc::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = sleep(wait) => {}
+                _ = shutdown.changed() => return,
+            }
+            if let Err(err) = self.run_scheduled(entry, shutdown.clone()).await {
+                error!("{command} failed: {err}");
+            }
+            if *shutdown.borrow() {
+                return;
+            }
+        }
+    }
+    async fn run_scheduled(
+        &self,
+        entry: &ScheduleEntry,
+        mut shutdown: watch::Receiver<bool>,
+    ) -> Result<(), Error> {
+        let command = entry.command();
+        let Some(lock) = self.repository.try_lock(&command).await? else {
+            warn!("{command} is still running, skipping");
+            return Ok(());
+        };
+        let application = self.for_new_run();
+        let runs = application.runs();
+        let id = runs.start(entry.game, &command).await?;
+        let result = tokio::select! {
+            result = application.run_stage(entry) => {
+                result.map_err(|err| err.to_string())
+            }
+            _ = shutdown.changed() => Err("interrupted by SIGTERM".to_string()),
+        };
+        if let Err(err) = &result {
+            error!("{command}: {err}");
+        }
+        runs.finish(id, result.err()).await?;
+        lock.release().await?;
+        Ok(())
+    }
+    async fn run_stage(&self, entry: &ScheduleEntry) -> Result<(), Error> {
+        let locale = entry.locale;
+        let region = entry.region;
+        match entry.task {
+            Task::Pipeline => {
+                let job = entry.command();
+                self.scrape(entry.game, &job, false, None, locale, region)
+                    .await?
+            }
+            Task::PtcgPrepare => self.ptcg(region).update_rarity().await?,
+            Task::YugiohForbiddenLimited => self.yugioh(locale).update_forbidden_limited().await?,
+            Task::PtcgJpCard => self.ptcg_jp().update_cards().await?,
+            Task::PtcgJpRarity => self.ptcg_jp().update_rarity().await?,
+        }
+        Ok(())
+    }
+}
+
+async fn wait_for_shutdown() {
+    #[cfg(unix)]
+    {
+        let mut terminate = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = terminate.recv() => {}
+            _ = signal::ctrl_c() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = signal::ctrl_c().await;
+}
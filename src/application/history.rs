@@ -0,0 +1,40 @@
+use chrono::{DateTime, Utc};
+
+use crate::{
+    domain::HistoryTable,
+    error::Error,
+    repository::{history::CardVersion, Repository},
+};
+
+pub struct History {
+    pub repository: Repository,
+}
+
+impl History {
+    pub async fn export_versions<W: std::io::Write>(
+        &self,
+        table: HistoryTable,
+        card_key: &str,
+        w: W,
+    ) -> Result<(), Error> {
+        let versions = self.repository.history().versions(table, card_key).await?;
+        write_jsonl(&versions, w)
+    }
+    pub async fn export_as_of<W: std::io::Write>(
+        &self,
+        table: HistoryTable,
+        at: DateTime<Utc>,
+        w: W,
+    ) -> Result<(), Error> {
+        let versions = self.repository.history().as_of(table, at).await?;
+        write_jsonl(&versions, w)
+    }
+}
+
+fn write_jsonl<W: std::io::Write>(versions: &[CardVersion], mut w: W) -> Result<(), Error> {
+    for version in versions {
+        serde_json::to_writer(&mut w, version)?;
+        writeln!(w)?;
+    }
+    Ok(())
+}
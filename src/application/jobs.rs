@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use futures::TryStreamExt;
+use tokio::time::sleep;
+use tracing::{info, warn};
+
+use crate::{
+    domain::{JobPayload, JobQueue},
+    error::Error,
+    repository::Repository,
+};
+
+use super::{one_piece::OnePiece, ptcg::Ptcg, ptcg_jp::PtcgJp, ws::Ws};
+
+const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct Jobs {
+    pub repository: Repository,
+    pub ptcg: Ptcg,
+    pub ptcg_jp: PtcgJp,
+    pub ws: Ws,
+    pub one_piece: OnePiece,
+}
+
+impl Jobs {
+    pub async fn seed(&self, queue: JobQueue) -> Result<u64, Error> {
+        let payloads: Vec<JobPayload> = match queue {
+            JobQueue::PtcgFetchable => {
+                self.repository
+                    .pokemon()
+                    .get_fetchable(None)
+                    .map_ok(|(code, expansion_code, region)| JobPayload::PtcgFetchable {
+                        code,
+                        expansion_code,
+                        region,
+                    })
+                    .try_collect()
+                    .await?
+            }
+            JobQueue::TcgCollectorLink => self
+                .repository
+                .get_ptcg_jp_expansions_links()
+                .await?
+                .into_iter()
+                .map(|url| JobPayload::TcgCollectorLink { url })
+                .collect(),
+            JobQueue::WsPage => (1..=self.ws.scraper.get_total_pages().await?)
+                .map(|page| JobPayload::WsPage { page })
+                .collect(),
+            JobQueue::OnePieceSeries => self
+                .one_piece
+                .scraper
+                .set()
+                .await?
+                .into_iter()
+                .map(|series| JobPayload::OnePieceSeries { series })
+                .collect(),
+        };
+        let inserted = self.repository.jobs().enqueue(&payloads).await?;
+        info!("{queue}: enqueued {inserted} of {} jobs", payloads.len());
+        Ok(inserted)
+    }
+    // drain the queue; safe to run in several processes at once
+    pub async fn work(&self, queue: JobQueue, lease: Duration) -> Result<(), Error> {
+        let jobs = self.repository.jobs();
+        loop {
+            let Some(job) = jobs.claim(queue, lease).await? else {
+                // failed jobs wait out their backoff, leased ones may come back when a worker dies
+                match jobs.next_claimable(queue).await? {
+                    Some(wait) => {
+                        sleep(wait.max(MIN_POLL_INTERVAL)).await;
+                        continue;
+                    }
+                    None => break,
+                }
+            };
+            match self.process(&job.payload).await {
+                Ok(()) => jobs.complete(&job).await?,
+                Err(err) => {
+                    warn!(
+                        "{queue}: job {} attempt {} failed: {err}",
+                        job.id, job.attempts
+                    );
+                    jobs.fail(&job, &err.to_string()).await?;
+                }
+            }
+        }
+        info!("{queue}: no job left");
+        Ok(())
+    }
+    async fn process(&self, payload: &JobPayload) -> Result<(), Error> {
+        match payload {
+            JobPayload::PtcgFetchable {
+                code,
+                expansion_code,
+                region,
+            } => {
+                self.ptcg
+                    .for_region(*region)
+                    .update_printing(code, expansion_code)
+                    .await?;
+                Ok(())
+            }
+            JobPayload::TcgCollectorLink { url } => self.ptcg_jp.save_link_html(url).await,
+            JobPayload::WsPage { page } => self.ws.scrape_page(*page).await,
+            JobPayload::OnePieceSeries { series } => self.one_piece.scrape_series(series).await,
+        }
+    }
+    pub async fn retry_dead(&self, queue: JobQueue) -> Result<(), Error> {
+        let count = self.repository.jobs().retry_dead(queue).await?;
+        info!("{queue}: {count} dead jobs back to pending");
+        Ok(())
+    }
+    pub async fn export_stats<W: std::io::Write>(&self, w: W) -> Result<(), Error> {
+        let mut wtr = csv::Writer::from_writer(w);
+        for stats in self.repository.jobs().stats().await? {
+            wtr.serialize(stats)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
@@ -1,26 +1,62 @@
+mod daemon;
+mod history;
+mod jobs;
 mod one_piece;
+mod pipeline;
 pub mod ptcg;
 mod ptcg_jp;
+mod runs;
+mod scrape_errors;
 mod ws;
 mod yugioh;
 
-use google_cloud_storage::http::objects::upload::{Media, UploadObjectRequest, UploadType};
+use google_cloud_storage::{
+    client::ClientConfig,
+    http::objects::upload::{Media, UploadObjectRequest, UploadType},
+};
 
-use self::{one_piece::OnePiece, ptcg::Ptcg, ptcg_jp::PtcgJp, ws::Ws, yugioh::Yugioh};
+use self::{
+    history::History, jobs::Jobs, one_piece::OnePiece, pipeline::Pipeline, ptcg::Ptcg,
+    ptcg_jp::PtcgJp, runs::Runs, scrape_errors::ScrapeErrors, ws::Ws, yugioh::Yugioh,
+};
 use crate::{
-    repository::Repository,
+    domain::{Game, PtcgRegion, YugiohLocale},
+    error::Error,
+    repository::{lock::AdvisoryLock, Repository},
     scraper::{
-        one_piece::OnePieceScraper, pokemon_wiki::PokemonWikiScraper, ptcg::PtcgScraper,
-        tcg_collector::TcgCollectorScraper, ws::WsScraper, yugioh::YugiohScraper,
+        client::{FetchMode, HttpClient, HttpConfig},
+        one_piece::OnePieceScraper,
+        pokemon_wiki::PokemonWikiScraper,
+        ptcg::PtcgScraper,
+        tcg_collector::TcgCollectorScraper,
+        webdriver::{SessionPool, WebDriverConfig},
+        ws::WsScraper,
+        yugioh::YugiohScraper,
     },
 };
-use std::{borrow::Cow, io::Write, path::Path};
+use std::{
+    borrow::Cow,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
-async fn download<T: AsRef<Path>>(url: url::Url, save_path: T) -> Result<(), crate::error::Error> {
-    let result = reqwest::get(url).await?;
-    let paths = result.url().path_segments().unwrap();
-    let file_name = paths.last().unwrap();
+async fn download<T: AsRef<Path>>(
+    client: &HttpClient,
+    url: url::Url,
+    save_path: T,
+) -> Result<(), Error> {
+    let result = client.get(url.as_str()).await?;
+    let file_name = result
+        .url()
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| Error::NoFileName(result.url().to_string()))?;
     let save_path = save_path.as_ref().join(file_name);
+    write_file(result, save_path).await
+}
+
+async fn write_file<T: AsRef<Path>>(result: reqwest::Response, save_path: T) -> Result<(), Error> {
     let mut file = std::fs::OpenOptions::new()
         .create(true)
         .write(true)
@@ -34,17 +70,24 @@ async fn download<T: AsRef<Path>>(url: url::Url, save_path: T) -> Result<(), cra
 #[derive(Clone)]
 pub struct GcsDownloader {
     pub client: google_cloud_storage::client::Client,
+    pub http_client: HttpClient,
     pub bucket: String,
     pub base_path: String,
 }
 
 impl GcsDownloader {
-    async fn download(&self, url: url::Url) -> Result<(), crate::error::Error> {
+    async fn download(&self, url: url::Url) -> Result<(), Error> {
         let mut iter = url.path_segments().unwrap().rev();
         let filename = iter.next().unwrap();
         let folder = iter.next().unwrap();
-        let resp = reqwest::get(url.clone()).await.unwrap();
-        let mut media = Media::new(format!("{}/{folder}/{filename}", self.base_path));
+        self.upload(&url, &format!("{folder}/{filename}")).await?;
+        Ok(())
+    }
+    // returns the object path under the bucket
+    async fn upload(&self, url: &url::Url, name: &str) -> Result<String, Error> {
+        let resp = self.http_client.get(url.as_str()).await?;
+        let path = format!("{}/{name}", self.base_path);
+        let mut media = Media::new(path.clone());
         media.content_type = Cow::from("image/jpeg");
         let _result = self
             .client
@@ -58,53 +101,183 @@ impl GcsDownloader {
             )
             .await
             .unwrap();
-        Ok(())
+        Ok(path)
+    }
+}
+
+// where downloaded card images end up, ./images/ or a GCS bucket
+enum ImageStore {
+    Local(PathBuf),
+    Gcs(Box<GcsDownloader>),
+}
+
+impl ImageStore {
+    // a GCS bucket when given, ./images/ otherwise
+    async fn new(http_client: &HttpClient, bucket: Option<&str>, base_path: &str) -> Self {
+        match bucket {
+            Some(bucket) => {
+                let config = ClientConfig::default().with_auth().await.unwrap();
+                ImageStore::Gcs(Box::new(GcsDownloader {
+                    client: google_cloud_storage::client::Client::new(config),
+                    http_client: http_client.clone(),
+                    bucket: bucket.to_string(),
+                    base_path: base_path.to_string(),
+                }))
+            }
+            None => ImageStore::Local("./images/".into()),
+        }
+    }
+    // returns the location written to exports
+    async fn save(&self, client: &HttpClient, url: url::Url, name: &str) -> Result<String, Error> {
+        match self {
+            ImageStore::Local(dir) => {
+                let path = dir.join(name);
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                write_file(client.get(url.as_str()).await?, &path).await?;
+                Ok(path.to_string_lossy().into_owned())
+            }
+            ImageStore::Gcs(downloader) => {
+                let path = downloader.upload(&url, name).await?;
+                Ok(format!(
+                    "https://storage.googleapis.com/{}/{path}",
+                    downloader.bucket
+                ))
+            }
+        }
     }
 }
 
 pub struct Application {
     repository: Repository,
+    client: HttpClient,
+    sessions: SessionPool,
 }
 
 impl Application {
-    pub fn new(url: &str) -> Self {
+    pub fn new(url: &str, mode: FetchMode) -> Self {
         let repository = Repository::from_dsn(url).unwrap();
-        Self { repository }
+        let client = HttpClient::new(HttpConfig::from_env())
+            .unwrap()
+            .with_archive(repository.archive(), mode);
+        let sessions = SessionPool::new(WebDriverConfig::from_env());
+        Self {
+            repository,
+            client,
+            sessions,
+        }
     }
-    pub fn ptcg(&self) -> Ptcg {
-        let scraper = PtcgScraper::new();
+    // idle webdriver sessions are not closed on drop
+    pub async fn close(&self) {
+        self.sessions.close().await;
+    }
+    // the lock the daemon takes for a scheduled command, None while another process holds it
+    pub async fn try_lock(&self, command: &str) -> Result<Option<AdvisoryLock>, Error> {
+        Ok(self.repository.try_lock(command).await?)
+    }
+    // job names the run and its checkpoint, e.g. "scrape yugioh --locale ko"
+    pub async fn scrape(
+        &self,
+        game: Game,
+        job: &str,
+        download_images: bool,
+        bucket: Option<&str>,
+        locale: YugiohLocale,
+        region: PtcgRegion,
+    ) -> Result<(), Error> {
+        let images = if download_images {
+            Some(ImageStore::new(&self.client, bucket, "images").await)
+        } else {
+            None
+        };
+        let pipeline = Pipeline {
+            repository: self.repository.clone(),
+            client: self.client.clone(),
+            images,
+        };
+        match game {
+            Game::Ptcg => pipeline.run(&self.ptcg(region).scraper, job).await,
+            Game::PtcgJp => pipeline.run(&self.ptcg_jp().scraper, job).await,
+            Game::Yugioh => pipeline.run(&self.yugioh(locale).scraper, job).await,
+            Game::Ws => pipeline.run(&self.ws().scraper, job).await,
+            Game::OnePiece => pipeline.run(&self.one_piece().scraper, job).await,
+        }
+    }
+    pub fn ptcg(&self, region: PtcgRegion) -> Ptcg {
+        let scraper = PtcgScraper::new(self.client.clone(), region);
         Ptcg {
             repository: self.repository.clone(),
             scraper,
-            wiki_scraper: PokemonWikiScraper::new(),
+            wiki_scraper: PokemonWikiScraper::new(self.client.clone()),
+            client: self.client.clone(),
         }
     }
     pub fn one_piece(&self) -> OnePiece {
-        let scraper = OnePieceScraper {};
+        let scraper = OnePieceScraper {
+            client: self.client.clone(),
+        };
         OnePiece {
+            client: self.client.clone(),
             scraper,
             repository: self.repository.clone(),
         }
     }
-    pub fn yugioh(&self) -> Yugioh {
-        let scraper = YugiohScraper::new();
+    pub fn yugioh(&self, locale: YugiohLocale) -> Yugioh {
+        let scraper = YugiohScraper::new(self.client.clone(), self.sessions.clone(), locale);
         Yugioh {
             scraper,
             repository: self.repository.clone(),
+            client: self.client.clone(),
         }
     }
     pub fn ws(&self) -> Ws {
-        let scraper = WsScraper {};
+        let scraper = WsScraper {
+            client: self.client.clone(),
+        };
         Ws {
+            client: self.client.clone(),
             scraper,
             repository: self.repository.clone(),
         }
     }
     pub fn ptcg_jp(&self) -> PtcgJp {
-        let scraper = TcgCollectorScraper {};
+        let scraper = TcgCollectorScraper {
+            client: self.client.clone(),
+        };
         PtcgJp {
             scraper,
             repository: self.repository.clone(),
         }
     }
+    pub fn history(&self) -> History {
+        History {
+            repository: self.repository.clone(),
+        }
+    }
+    pub fn jobs(&self) -> Jobs {
+        Jobs {
+            repository: self.repository.clone(),
+            ptcg: self.ptcg(PtcgRegion::default()),
+            ptcg_jp: self.ptcg_jp(),
+            ws: self.ws(),
+            one_piece: self.one_piece(),
+        }
+    }
+    pub fn runs(&self) -> Runs {
+        Runs {
+            repository: self.repository.clone(),
+            client: self.client.clone(),
+        }
+    }
+    pub fn scrape_errors(&self) -> ScrapeErrors {
+        ScrapeErrors {
+            repository: self.repository.clone(),
+            ptcg: self.ptcg(PtcgRegion::default()),
+            ptcg_jp: self.ptcg_jp(),
+            yugioh: self.yugioh(YugiohLocale::default()).scraper,
+            ws: self.ws(),
+            one_piece: self.one_piece(),
+        }
+    }
 }
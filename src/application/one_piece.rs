@@ -1,8 +1,9 @@
 use crate::{
-    error::Error,
+    domain::Game,
+    error::{CardFailure, Error},
     export::{export_csv::ExportCsv, one_piece_csv::OnePieceProductsCsv},
     repository::Repository,
-    scraper::one_piece::OnePieceScraper,
+    scraper::{client::HttpClient, one_piece::OnePieceScraper},
 };
 use futures::TryStreamExt;
 
@@ -11,6 +12,7 @@ use super::download;
 pub struct OnePiece {
     pub scraper: OnePieceScraper,
     pub repository: Repository,
+    pub client: HttpClient,
 }
 
 impl OnePiece {
@@ -20,21 +22,28 @@ impl OnePiece {
             .map_err(Error::from)
             .try_for_each(|card| async move {
                 let u = url::Url::parse(&card.img_src.clone())?;
-                download(u, "./images/").await?;
+                download(&self.client, u, "./images/").await?;
                 Ok(())
             })
             .await?;
         Ok(())
     }
-    pub async fn scrape_one_piece(&self) -> Result<(), Error> {
-        let sets = self.scraper.set().await?;
-        for set in sets {
-            for card in self.scraper.scrape_cards(&set).await? {
-                self.repository.upsert_one_piece(card.unwrap()).await;
+    pub async fn scrape_series(&self, series: &str) -> Result<(), Error> {
+        for card in self.scraper.scrape_cards(series).await? {
+            match card {
+                Ok(card) => self.repository.upsert_one_piece(card).await?,
+                Err(failure) => self.record_failure(&failure).await?,
             }
         }
         Ok(())
     }
+    async fn record_failure(&self, failure: &CardFailure) -> Result<(), Error> {
+        self.repository
+            .scrape_errors()
+            .record(Game::OnePiece, failure)
+            .await?;
+        Ok(())
+    }
     pub async fn scrape_one_piece_products(&self) {
         let products = self.scraper.products().await;
         println!("{:#?}", products);
@@ -54,8 +63,13 @@ impl OnePiece {
         let mut wtr = csv::Writer::from_writer(w);
         for set in sets {
             for card in self.scraper.scrape_cards(&set).await? {
-                let c: ExportCsv = card.unwrap().into();
-                wtr.serialize(c).unwrap();
+                match card {
+                    Ok(card) => {
+                        let c: ExportCsv = card.into();
+                        wtr.serialize(c).unwrap();
+                    }
+                    Err(failure) => self.record_failure(&failure).await?,
+                }
             }
         }
         wtr.flush().unwrap();
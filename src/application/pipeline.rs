@@ -0,0 +1,102 @@
+use tracing::{info, warn};
+
+use crate::{
+    error::{CardFailure, Error, ErrorCode},
+    repository::{card_store::CardStore, Repository},
+    scraper::{card_source::CardSource, client::HttpClient},
+};
+
+use super::ImageStore;
+
+pub struct Pipeline {
+    pub repository: Repository,
+    pub client: HttpClient,
+    // None unless card images are downloaded too
+    pub images: Option<ImageStore>,
+}
+
+impl Pipeline {
+    // job names the checkpoint, a crashed run resumes after the last finished expansion
+    pub async fn run<S>(&self, source: &S, job: &str) -> Result<(), Error>
+    where
+        S: CardSource,
+        Repository: CardStore<S>,
+    {
+        let game = source.game();
+        let checkpoint = self.repository.checkpoint(job);
+        let expansions = checkpoint
+            .remaining(source.expansions().await?, |e| source.expansion_key(e))
+            .await?;
+        let total = expansions.len();
+        for (n, expansion) in expansions.iter().enumerate() {
+            self.repository.save_expansion(expansion).await?;
+            let card_refs = source.card_refs(expansion).await?;
+            info!(
+                "{game}: expansion {}/{total}, {} cards",
+                n + 1,
+                card_refs.len()
+            );
+            for card_ref in card_refs {
+                let card_ref = match card_ref {
+                    Ok(card_ref) => card_ref,
+                    Err(failure) => {
+                        warn!("{game}: {} {}", failure.url, failure.message);
+                        self.repository
+                            .scrape_errors()
+                            .record(game, &failure)
+                            .await?;
+                        continue;
+                    }
+                };
+                let url = source.card_url(&card_ref);
+                let identifier = source.card_identifier(&card_ref);
+                let card = match source.card_detail(card_ref).await {
+                    Ok(card) => card,
+                    Err(err) => {
+                        warn!("{game}: {url} {err}");
+                        let failure = CardFailure::new(
+                            &url,
+                            identifier,
+                            ErrorCode::Detail(err.to_string()),
+                            None,
+                        );
+                        self.repository
+                            .scrape_errors()
+                            .record(game, &failure)
+                            .await?;
+                        continue;
+                    }
+                };
+                let image = match (&self.images, source.image_url(&card)) {
+                    (Some(images), Some(image_url)) => {
+                        let name = source.image_name(&card, &image_url);
+                        match images.save(&self.client, image_url, &name).await {
+                            Ok(location) => Some(location),
+                            // the card is saved without its image, download-images catches up
+                            Err(err) => {
+                                warn!("{game}: {url} image {err}");
+                                let failure = CardFailure::new(
+                                    &url,
+                                    identifier,
+                                    ErrorCode::Image(err.to_string()),
+                                    None,
+                                );
+                                self.repository
+                                    .scrape_errors()
+                                    .record(game, &failure)
+                                    .await?;
+                                None
+                            }
+                        }
+                    }
+                    _ => None,
+                };
+                self.repository.save_card(card, image).await?;
+            }
+            checkpoint.save(&source.expansion_key(expansion)).await?;
+        }
+        checkpoint.clear().await?;
+        info!("{game}: done, {total} expansions");
+        Ok(())
+    }
+}
@@ -1,96 +1,408 @@
+use super::{download, write_file};
 use crate::{
-    domain::{PokemonCard, PtcgRarity},
+    domain::{PokemonCard, PtcgRarity, PtcgRegion},
     error::Error,
-    repository::{pokemon, Repository},
-    scraper::{pokemon_wiki::PokemonWikiScraper, ptcg::PtcgScraper},
-    strategy::{ManualStrategy, PtcgStrategy, Source, TcgCollectorStrategy, WikiStrategy},
+    repository::{strategy::StoredStrategy, Repository},
+    rotation::{PtcgLegality, Rotation, Rotations},
+    scraper::{
+        client::HttpClient,
+        pokemon_wiki::{PokemonWikiCard, PokemonWikiScraper},
+        ptcg::PtcgScraper,
+        tcg_collector::{card_image_url, TcgCollectorCardDetail},
+    },
+    strategy::{
+        card_number, diff_sources, in_range, ManualStrategy, PtcgStrategy, Source, StrategyError,
+        TcgCollectorStrategy, WikiStrategy,
+    },
     PtcgStrategyRow,
 };
+use chrono::NaiveDate;
 use futures::{StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::collections::HashSet;
 use strum::IntoEnumIterator;
-use url::Url;
+use tracing::{info, warn};
 
-use super::download;
+#[derive(Serialize)]
+struct LegalityRow {
+    code: String,
+    region: PtcgRegion,
+    expansion_code: String,
+    name: String,
+    number: Option<String>,
+    rarity: Option<String>,
+    regulation_mark: Option<String>,
+    legality: PtcgLegality,
+    rotates_out: Option<NaiveDate>,
+}
+
+#[derive(Serialize)]
+struct ExportedStrategy {
+    exp: String,
+    name: String,
+    strategy: String,
+    version: i32,
+    author: Option<String>,
+}
 
 #[derive(Clone)]
 pub struct Ptcg {
     pub repository: Repository,
     pub scraper: PtcgScraper,
     pub wiki_scraper: PokemonWikiScraper,
+    pub client: HttpClient,
 }
 
 impl Ptcg {
+    pub fn region(&self) -> PtcgRegion {
+        self.scraper.region()
+    }
+    pub fn for_region(&self, region: PtcgRegion) -> Self {
+        Self {
+            scraper: PtcgScraper::new(self.client.clone(), region),
+            ..self.clone()
+        }
+    }
     pub async fn strategy_analyze(
         &self,
         sources: Vec<Source>,
         record: PtcgStrategyRow,
+        strategy_id: Option<i32>,
     ) -> Result<(), Error> {
+        // the checks of --dry-run, so a bad source stops the row before anything is fetched
+        let mut errors = sources.iter().flat_map(Source::validate);
+        if let Some(err) = errors.next() {
+            for other in errors {
+                warn!("{}: {other}", record.exp);
+            }
+            return Err(Error::Strategy(record.exp, err));
+        }
         for source in sources {
             match source {
                 Source::Ptcg(PtcgStrategy::All) => {
-                    // let count = self
-                    //     .repository
-                    //     .get_fetchable_by_code(&record.exp)
-                    //     .count()
-                    //     .await;
-                    // if count == 0 {
-                    //     let fetchable_codes =
-                    //         self.scraper.get_fetchables_by_exp(&record.exp).await?;
-                    //     self.repository
-                    //         .upsert_fetchable(fetchable_codes, &record.exp)
-                    //         .await?;
-                    // }
-                    // let mut card = self
-                    //     .scraper
-                    //     .fetch_printing_detail(&format!(
-                    //         "https://asia.pokemon-card.com/tw/card-search/detail/{}/",
-                    //         record.exp
-                    //     ))
-                    //     .await?;
-                    // card.set_code = Some(record.exp.clone());
-                    // self.repository.upsert_the_ptcg_card(&card).await;
-                    // self.repository.fetched(&record.exp).await;
+                    let fetchable_codes = self.scraper.get_fetchables_by_exp(&record.exp).await?;
+                    self.repository
+                        .pokemon()
+                        .upsert_fetchable(fetchable_codes.clone(), &record.exp, self.region())
+                        .await?;
+                    for code in fetchable_codes {
+                        // tagged by the code the printing is stored under, not the fetched one
+                        let code = self.update_printing(&code, &record.exp).await?;
+                        if let Some(strategy_id) = strategy_id {
+                            self.repository
+                                .pokemon()
+                                .set_printing_strategy(&code, self.region(), strategy_id)
+                                .await?;
+                        }
+                    }
+                }
+                Source::Ptcg(PtcgStrategy::Pic) => {
+                    let codes: Vec<String> = self
+                        .repository
+                        .pokemon()
+                        .get_fetchable_by_code(&record.exp, self.region())
+                        .try_collect()
+                        .await?;
+                    for code in codes {
+                        if let Some(image_url) = self.scraper.card_image_url(&code) {
+                            download(&self.client, image_url, "./images/").await?;
+                        }
+                    }
                 }
-                Source::Ptcg(PtcgStrategy::Pic) => {}
                 Source::Wiki(WikiStrategy::Data(data)) => {
-                    let cards = self
+                    let mut cards = self
                         .wiki_scraper
                         .fetch_card_data_by_exp_url(data.url().as_str(), &record.exp)
                         .await?;
-                    self.repository.upsert_pokewiki(cards).await?;
+                    if let Some(range) = data.range() {
+                        cards.retain(|card| in_range(&range, &card.number));
+                    }
+                    self.repository.upsert_pokewiki(cards, strategy_id).await?;
+                }
+                Source::TcgCollector(TcgCollectorStrategy::Pic(data)) => {
+                    let range = data.range();
+                    let cards = self
+                        .repository
+                        .get_tc_details_by_exps(std::slice::from_ref(&record.exp))
+                        .await?;
+                    for card in cards.iter().filter(|c| in_range(&range, &c.number)) {
+                        if let Some(number) = card_number(&card.number) {
+                            self.save_tcg_collector_image(card, &record.exp, number)
+                                .await?;
+                        }
+                    }
+                }
+                Source::TcgCollector(TcgCollectorStrategy::PicByName(data)) => {
+                    let cards = self.repository.get_tc_details_by_exps(data.exps()).await?;
+                    let printings = self
+                        .repository
+                        .pokemon()
+                        .get_ptcg_printing_names(&record.exp, self.region())
+                        .await?;
+                    for (number, name_en) in printings {
+                        let (Some(number), Some(name_en)) = (card_number(&number), name_en) else {
+                            continue;
+                        };
+                        // exps are listed by preference, the first match wins
+                        if let Some(card) = cards.iter().find(|c| c.name == name_en) {
+                            self.save_tcg_collector_image(card, &record.exp, number)
+                                .await?;
+                        }
+                    }
+                }
+                Source::TcgCollector(TcgCollectorStrategy::PicMappings(data)) => {
+                    let mappings = data
+                        .mappings()
+                        .map_err(|err| Error::Strategy(record.exp.clone(), err))?;
+                    for (number, exp, jp_number) in mappings {
+                        let cards = self
+                            .repository
+                            .get_tc_details_by_exps(&[exp.to_string()])
+                            .await?;
+                        match cards
+                            .iter()
+                            .find(|c| card_number(&c.number) == Some(jp_number))
+                        {
+                            Some(card) => {
+                                self.save_tcg_collector_image(card, &record.exp, number)
+                                    .await?
+                            }
+                            None => warn!("{exp} {jp_number} not found in tcg_collector"),
+                        }
+                    }
+                }
+                Source::Manual(ManualStrategy::Data(data)) => {
+                    let cards = data
+                        .card_data()
+                        .iter()
+                        .map(|card| PokemonWikiCard {
+                            number: card.number().to_string(),
+                            name: card.name().to_string(),
+                            rarity: PtcgRarity::Unknown,
+                            exp_code: record.exp.clone(),
+                        })
+                        .collect();
+                    self.repository.upsert_pokewiki(cards, strategy_id).await?;
                 }
-                Source::TcgCollector(TcgCollectorStrategy::Pic(_data)) => {}
-                Source::TcgCollector(TcgCollectorStrategy::PicByName(_data)) => {}
-                Source::TcgCollector(TcgCollectorStrategy::PicMappings(_data)) => {}
-                Source::Manual(ManualStrategy::Data(_card_data)) => {}
             }
         }
         Ok(())
     }
-    pub async fn download_all_image(&self) -> Result<(), Error> {
-        let poke_repo = self.repository.pokemon();
-        let codes = poke_repo.get_ptcg_card_codes();
-        codes
-            .map_err(Error::from)
-            .try_for_each(|code| async move {
-                dbg!(&code);
-                let code: i32 = code.parse().unwrap();
-                let image_url = format!(
-                    "https://asia.pokemon-card.com/tw/card-img/tw{:08}.png",
-                    code
-                );
-                let image_url = Url::parse(&image_url)?;
-                download(image_url, "./images/").await?;
-                Ok(())
-            })
+    // prints the planned actions of every row and returns how many problems were found
+    pub async fn strategy_dry_run<W: std::io::Write>(
+        &self,
+        rows: Vec<PtcgStrategyRow>,
+        mut w: W,
+    ) -> Result<usize, Error> {
+        let expansions = self.known_expansions().await?;
+        let mut problems = 0;
+        for row in rows {
+            writeln!(w, "{} {}", row.exp, row.name)?;
+            let (sources, errors) = check_strategy_row(&row, &expansions);
+            for source in sources {
+                writeln!(w, "  {source}")?;
+            }
+            for error in &errors {
+                writeln!(w, "  error: {error}")?;
+            }
+            problems += errors.len();
+        }
+        Ok(problems)
+    }
+    async fn known_expansions(&self) -> Result<HashSet<String>, Error> {
+        let expansions = self
+            .repository
+            .pokemon()
+            .get_ptcg_exp_codes(self.region())
+            .try_collect()
             .await?;
+        Ok(expansions)
+    }
+    // stores a new version for every row whose sources changed; nothing when a row is invalid
+    pub async fn import_strategies<W: std::io::Write>(
+        &self,
+        rows: Vec<PtcgStrategyRow>,
+        author: Option<&str>,
+        mut w: W,
+    ) -> Result<(), Error> {
+        let expansions = self.known_expansions().await?;
+        let mut parsed = vec![];
+        let mut problems = 0;
+        for row in rows {
+            let (_, errors) = check_strategy_row(&row, &expansions);
+            for error in &errors {
+                writeln!(w, "{}: {error}", row.exp)?;
+            }
+            problems += errors.len();
+            if errors.is_empty() {
+                let sources: serde_json::Value = serde_json::from_str(&row.strategy)?;
+                parsed.push((row, sources));
+            }
+        }
+        if problems > 0 {
+            return Err(Error::InvalidStrategies(problems));
+        }
+        let strategies = self.repository.strategies();
+        for (row, sources) in parsed {
+            let saved = strategies
+                .save(&row.exp, self.region(), &row.name, &sources, author)
+                .await?;
+            match saved {
+                Some(version) => writeln!(w, "{} v{version}", row.exp)?,
+                None => writeln!(w, "{} unchanged", row.exp)?,
+            }
+        }
+        Ok(())
+    }
+    // the latest strategies in the CSV format import reads
+    pub async fn export_strategies<W: std::io::Write>(
+        &self,
+        exp: Option<&str>,
+        w: W,
+    ) -> Result<(), Error> {
+        let strategies = self
+            .repository
+            .strategies()
+            .list_latest(self.region())
+            .await?;
+        let mut wtr = csv::Writer::from_writer(w);
+        for strategy in strategies {
+            if exp.is_some_and(|exp| exp != strategy.exp_code) {
+                continue;
+            }
+            wtr.serialize(ExportedStrategy {
+                exp: strategy.exp_code,
+                name: strategy.name,
+                strategy: strategy.sources.to_string(),
+                version: strategy.version,
+                author: strategy.author,
+            })?;
+        }
+        wtr.flush()?;
         Ok(())
     }
+    // compares with the version before `to` when from is None, `to` defaults to the latest
+    pub async fn diff_strategy<W: std::io::Write>(
+        &self,
+        exp: &str,
+        from: Option<i32>,
+        to: Option<i32>,
+        mut w: W,
+    ) -> Result<(), Error> {
+        let strategies = self.repository.strategies();
+        let to = strategies
+            .find(exp, self.region(), to)
+            .await?
+            .ok_or_else(|| {
+                Error::Strategy(exp.to_string(), StrategyError::NotStored(exp.to_string()))
+            })?;
+        let from = match from.unwrap_or(to.version - 1) {
+            0 => None,
+            version => Some(
+                strategies
+                    .find(exp, self.region(), Some(version))
+                    .await?
+                    .ok_or_else(|| {
+                        Error::Strategy(
+                            exp.to_string(),
+                            StrategyError::NotStored(format!("{exp} v{version}")),
+                        )
+                    })?,
+            ),
+        };
+        let sources = |strategy: Option<&StoredStrategy>| -> Vec<serde_json::Value> {
+            strategy
+                .and_then(|s| s.sources.as_array().cloned())
+                .unwrap_or_default()
+        };
+        writeln!(w, "--- {exp} v{}", from.as_ref().map_or(0, |s| s.version))?;
+        writeln!(w, "+++ {exp} v{}", to.version)?;
+        if let Some(from) = from.as_ref().filter(|from| from.name != to.name) {
+            writeln!(w, "name: {} -> {}", from.name, to.name)?;
+        }
+        for line in diff_sources(&sources(from.as_ref()), &sources(Some(&to))) {
+            writeln!(w, "{line}")?;
+        }
+        Ok(())
+    }
+    // runs the stored strategies of exps, the latest of every expansion when exps is empty
+    pub async fn apply_strategies(
+        &self,
+        exps: &[String],
+        version: Option<i32>,
+    ) -> Result<(), Error> {
+        let repo = self.repository.strategies();
+        if exps.is_empty() && version.is_some() {
+            return Err(Error::VersionWithoutExpansion);
+        }
+        let strategies = if exps.is_empty() {
+            repo.list_latest(self.region()).await?
+        } else {
+            let mut strategies = vec![];
+            for exp in exps {
+                let strategy = repo
+                    .find(exp, self.region(), version)
+                    .await?
+                    .ok_or_else(|| {
+                        Error::Strategy(exp.clone(), StrategyError::NotStored(exp.clone()))
+                    })?;
+                strategies.push(strategy);
+            }
+            strategies
+        };
+        for strategy in strategies {
+            let sources: Vec<Source> = serde_json::from_value(strategy.sources.clone())
+                .map_err(|err| Error::Strategy(strategy.exp_code.clone(), err.into()))?;
+            info!("applying {} v{}", strategy.exp_code, strategy.version);
+            let record = PtcgStrategyRow {
+                exp: strategy.exp_code,
+                name: strategy.name,
+                strategy: strategy.sources.to_string(),
+            };
+            self.strategy_analyze(sources, record, Some(strategy.id))
+                .await?;
+            repo.applied(strategy.id).await?;
+        }
+        Ok(())
+    }
+    // every version of the expansion's strategy with the rows it last wrote
+    pub async fn strategy_history<W: std::io::Write>(&self, exp: &str, w: W) -> Result<(), Error> {
+        let versions = self
+            .repository
+            .strategies()
+            .versions(exp, self.region())
+            .await?;
+        let mut wtr = csv::Writer::from_writer(w);
+        for version in versions {
+            wtr.serialize(version)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+    // saved as {exp}-{number} so it lines up with the printing it stands in for
+    async fn save_tcg_collector_image(
+        &self,
+        card: &TcgCollectorCardDetail,
+        exp: &str,
+        number: i32,
+    ) -> Result<(), Error> {
+        let Some(image_url) = card_image_url(&card.html) else {
+            warn!("no image in {}", card.url);
+            return Ok(());
+        };
+        let ext = image_url
+            .path()
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_string())
+            .unwrap_or_else(|| "jpg".to_string());
+        let result = self.client.get(image_url.as_str()).await?;
+        write_file(result, format!("./images/{exp}-{number}.{ext}")).await
+    }
     pub async fn prepare_ptcg_expansions(&self) -> Result<(), Error> {
         let count = self
             .repository
             .pokemon()
-            .find_ptcg_expansion()
+            .find_ptcg_expansion(self.region())
             .count()
             .await;
         if count == 0 {
@@ -104,63 +416,107 @@ impl Ptcg {
         }
         Ok(())
     }
-    pub async fn update_ptcg_fetchable(&self) -> Result<(), Error> {
-        let poke_repo = self.repository.pokemon();
-        let codes = poke_repo.get_ptcg_exp_codes();
-        codes
-            .map_err(Error::from)
-            .try_for_each(|code| async move {
-                let count = self
-                    .repository
-                    .pokemon()
-                    .get_fetchable_by_code(&code)
-                    .count()
-                    .await;
-                if count == 0 {
-                    let fetchable_codes = self.scraper.get_fetchables_by_exp(&code).await?;
-                    self.repository
-                        .pokemon()
-                        .upsert_fetchable(fetchable_codes, &code)
-                        .await?;
-                }
-                Ok(())
-            })
+    // returns the code the printing is stored under
+    pub async fn update_printing(&self, code: &str, set_code: &str) -> Result<String, Error> {
+        let mut card = self
+            .scraper
+            .fetch_printing_detail(&self.scraper.detail_url(code))
             .await?;
-        Ok(())
-    }
-    pub async fn update_ptcg_printing(&self) -> Result<(), Error> {
-        self.repository
+        card.set_code = Some(set_code.to_string());
+        let stored_code = self
+            .repository
             .pokemon()
-            .get_fetchable()
-            .map_err(Error::from)
-            .try_for_each(|(code, set_code)| async move {
-                let mut card = self
-                    .scraper
-                    .fetch_printing_detail(&format!(
-                        "https://asia.pokemon-card.com/tw/card-search/detail/{code}/"
-                    ))
-                    .await?;
-                card.set_code = Some(set_code);
-                self.repository.pokemon().upsert_the_ptcg_card(&card).await;
-                self.repository.pokemon().fetched(&code).await;
-                Ok(())
-            })
+            .upsert_the_ptcg_card(&card)
             .await?;
-        Ok(())
+        self.repository.pokemon().fetched(code, self.region()).await;
+        Ok(stored_code)
     }
     pub async fn update_rarity(&self) -> Result<(), Error> {
-        for rarity in PtcgRarity::iter() {
+        let job = match self.region() {
+            PtcgRegion::Tw => "ptcg_update_rarity".to_string(),
+            region => format!("ptcg_update_rarity_{region}"),
+        };
+        let checkpoint = self.repository.checkpoint(&job);
+        let rarities = checkpoint
+            .remaining(PtcgRarity::iter().collect(), PtcgRarity::to_string)
+            .await?;
+        for rarity in rarities {
             let ids = self.scraper.rarity_ids(&rarity).await?;
             self.repository
                 .pokemon()
-                .update_the_ptcg_rarity(ids, &rarity)
+                .update_the_ptcg_rarity(ids, &rarity, self.region())
                 .await?;
+            checkpoint.save(&rarity.to_string()).await?;
         }
+        checkpoint.clear().await?;
         Ok(())
     }
-    pub async fn export_pokemon_trainer(&self) -> Result<Vec<PokemonCard>, Error> {
+    pub async fn export_pokemon_trainer(
+        &self,
+        region: Option<PtcgRegion>,
+    ) -> Result<Vec<PokemonCard>, Error> {
         let poke_repo = self.repository.pokemon();
-        let all_cards = poke_repo.get_all_pokemon_trainer_printing();
+        let all_cards = poke_repo.get_all_pokemon_trainer_printing(region);
         Ok(all_cards.collect().await)
     }
+    pub async fn set_rotation(&self, rotation: &Rotation) -> Result<(), Error> {
+        self.repository
+            .pokemon()
+            .upsert_ptcg_rotation(rotation)
+            .await?;
+        Ok(())
+    }
+    // printings leaving Standard at the next announced rotation, or every printing with all
+    pub async fn legality_report<W: std::io::Write>(
+        &self,
+        on: NaiveDate,
+        region: Option<PtcgRegion>,
+        all: bool,
+        w: W,
+    ) -> Result<(), Error> {
+        let poke_repo = self.repository.pokemon();
+        let rotations = Rotations::new(poke_repo.get_ptcg_rotations().await?);
+        let mut cards = poke_repo.get_all_pokemon_trainer_printing(region);
+        let mut wtr = csv::Writer::from_writer(w);
+        while let Some(card) = cards.next().await {
+            let mark = card.attributes.regulation_mark.as_deref();
+            let rotates_out = rotations.rotates_out(mark, on);
+            if !all && rotates_out.is_none() {
+                continue;
+            }
+            wtr.serialize(LegalityRow {
+                legality: rotations.legality(mark, on),
+                rotates_out,
+                code: card.id,
+                region: card.region,
+                expansion_code: card.set_id,
+                name: card.name,
+                number: card.number,
+                rarity: card.rarity,
+                regulation_mark: card.attributes.regulation_mark,
+            })?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+// the sources of a strategy CSV row and every problem --dry-run and import refuse
+fn check_strategy_row(
+    row: &PtcgStrategyRow,
+    expansions: &HashSet<String>,
+) -> (Vec<Source>, Vec<StrategyError>) {
+    let mut errors = vec![];
+    if !expansions.contains(&row.exp) {
+        errors.push(StrategyError::UnknownExpansion(row.exp.clone()));
+    }
+    let sources = match serde_json::from_str::<Vec<Source>>(&row.strategy) {
+        Ok(sources) => sources,
+        Err(err) => {
+            errors.push(err.into());
+            vec![]
+        }
+    };
+    errors.extend(sources.iter().flat_map(Source::validate));
+    (sources, errors)
 }
@@ -8,17 +8,9 @@ pub struct PtcgJp {
 }
 
 impl PtcgJp {
-    pub async fn update_exp(&self) -> Result<(), Error> {
-        let exps = self.scraper.fetch_exps().await?;
-        self.repository.save_ptcg_jp_expansions(exps).await?;
-        Ok(())
-    }
-    pub async fn save_html(&self) -> Result<(), Error> {
-        let links = self.repository.get_ptcg_jp_expansions_links().await?;
-        for link in links {
-            let details = self.scraper.fetch_card_detail_html(&link).await?;
-            self.repository.save_tcg_collector(details).await?;
-        }
+    pub async fn save_link_html(&self, link: &str) -> Result<(), Error> {
+        let details = self.scraper.fetch_card_detail_html(link).await?;
+        self.repository.save_tcg_collector(details).await?;
         Ok(())
     }
     pub async fn update_cards(&self) -> Result<(), Error> {
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    domain::Game,
+    error::Error,
+    repository::{changelog::ChangeRecord, run::RunRecord, Repository},
+    scraper::client::HttpClient,
+};
+
+pub struct Runs {
+    pub repository: Repository,
+    pub client: HttpClient,
+}
+
+impl Runs {
+    pub async fn start(&self, game: Game, command: &str) -> Result<i32, Error> {
+        let id = self.repository.runs().start(game, command).await?;
+        self.repository.current_run.set_id(id);
+        Ok(id)
+    }
+    pub async fn finish(&self, id: i32, error_message: Option<String>) -> Result<(), Error> {
+        if error_message.is_none() {
+            self.repository.changelog().record_removed(id).await?;
+        }
+        self.repository
+            .runs()
+            .finish(
+                id,
+                self.client.pages_fetched(),
+                &self.repository.current_run,
+                error_message,
+            )
+            .await?;
+        Ok(())
+    }
+    pub async fn export_csv<W: std::io::Write>(
+        &self,
+        game: Option<Game>,
+        limit: i64,
+        w: W,
+    ) -> Result<(), Error> {
+        let mut wtr = csv::Writer::from_writer(w);
+        for record in self.repository.runs().list(game, limit).await? {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+    pub async fn show<W: std::io::Write>(&self, id: i32, mut w: W) -> Result<(), Error> {
+        let Some(run) = self.repository.runs().find(id).await? else {
+            writeln!(w, "run {id} not found")?;
+            return Ok(());
+        };
+        write_run(&run, &mut w)?;
+        Ok(())
+    }
+    // one JSON object per line, for tools
+    pub async fn export_changelog<W: std::io::Write>(
+        &self,
+        id: i32,
+        mut w: W,
+    ) -> Result<(), Error> {
+        for change in self.repository.changelog().list(id).await? {
+            serde_json::to_writer(&mut w, &change)?;
+            writeln!(w)?;
+        }
+        Ok(())
+    }
+    pub async fn summarize_changelog<W: std::io::Write>(
+        &self,
+        id: i32,
+        mut w: W,
+    ) -> Result<(), Error> {
+        let changes = self.repository.changelog().list(id).await?;
+        write_summary(&changes, &mut w)?;
+        Ok(())
+    }
+}
+
+fn write_summary<W: std::io::Write>(changes: &[ChangeRecord], w: &mut W) -> std::io::Result<()> {
+    let count = |change: &str| changes.iter().filter(|c| c.change == change).count();
+    let mut fields: BTreeMap<&str, usize> = BTreeMap::new();
+    for field in changes.iter().filter_map(|c| c.field.as_deref()) {
+        *fields.entry(field).or_default() += 1;
+    }
+    writeln!(
+        w,
+        "{} new, {} removed, {} field changes",
+        count("new"),
+        count("removed"),
+        count("changed")
+    )?;
+    for (field, n) in fields {
+        writeln!(w, "  {field}: {n}")?;
+    }
+    for change in changes {
+        match change.change.as_str() {
+            "new" => writeln!(w, "+ {} {}", change.game, change.card_key)?,
+            "removed" => writeln!(w, "- {} {}", change.game, change.card_key)?,
+            _ => writeln!(
+                w,
+                "~ {} {} {}: {} -> {}",
+                change.game,
+                change.card_key,
+                change.field.as_deref().unwrap_or_default(),
+                change.old_value.as_deref().unwrap_or("-"),
+                change.new_value.as_deref().unwrap_or("-")
+            )?,
+        }
+    }
+    Ok(())
+}
+
+fn write_run<W: std::io::Write>(run: &RunRecord, w: &mut W) -> std::io::Result<()> {
+    writeln!(w, "run:       {}", run.id)?;
+    writeln!(w, "game:      {}", run.game)?;
+    writeln!(w, "command:   {}", run.command)?;
+    writeln!(w, "status:    {}", run.status)?;
+    writeln!(w, "started:   {}", run.started_at)?;
+    writeln!(
+        w,
+        "finished:  {}",
+        run.finished_at.as_deref().unwrap_or("-")
+    )?;
+    writeln!(w, "pages:     {}", run.pages_fetched)?;
+    writeln!(w, "inserted:  {}", run.cards_inserted)?;
+    writeln!(w, "updated:   {}", run.cards_updated)?;
+    writeln!(w, "unchanged: {}", run.cards_unchanged)?;
+    writeln!(w, "errors:    {}", run.error_count)?;
+    if let Some(message) = &run.error_message {
+        writeln!(w, "failure:   {message}")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use tracing::{info, warn};
+
+use crate::{
+    domain::{Game, PtcgRegion},
+    error::Error,
+    repository::{card_store::CardStore, Repository},
+    scraper::{tcg_collector::TcgCollectorCardRef, yugioh::YugiohScraper},
+};
+
+use super::{one_piece::OnePiece, ptcg::Ptcg, ptcg_jp::PtcgJp, ws::Ws};
+
+pub struct ScrapeErrors {
+    pub repository: Repository,
+    pub ptcg: Ptcg,
+    pub ptcg_jp: PtcgJp,
+    pub yugioh: YugiohScraper,
+    pub ws: Ws,
+    pub one_piece: OnePiece,
+}
+
+impl ScrapeErrors {
+    pub async fn export_csv<W: std::io::Write>(
+        &self,
+        game: Option<Game>,
+        include_resolved: bool,
+        w: W,
+    ) -> Result<(), Error> {
+        let mut wtr = csv::Writer::from_writer(w);
+        for record in self
+            .repository
+            .scrape_errors()
+            .list(game, include_resolved)
+            .await?
+        {
+            wtr.serialize(record)?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+    // re-scrape every page that still has unresolved failures, then mark them resolved;
+    // cards failing again are recorded as new rows
+    pub async fn requeue(&self, game: Option<Game>) -> Result<(), Error> {
+        let records = self.repository.scrape_errors().list(game, false).await?;
+        let mut pages: BTreeMap<(String, String, Option<String>), Vec<i32>> = BTreeMap::new();
+        for record in records {
+            pages
+                .entry((record.game, record.url, record.card_identifier))
+                .or_default()
+                .push(record.id);
+        }
+        let mut skipped = 0;
+        for ((game, url, identifier), ids) in pages {
+            let identifier = identifier.as_deref();
+            let requeued = match game.parse() {
+                Ok(Game::Ws) => match query_param(&url, "page").and_then(|p| p.parse().ok()) {
+                    Some(page_no) => {
+                        self.ws.scrape_page(page_no).await?;
+                        true
+                    }
+                    None => false,
+                },
+                Ok(Game::OnePiece) => match query_param(&url, "series") {
+                    Some(series) => {
+                        self.one_piece.scrape_series(&series).await?;
+                        true
+                    }
+                    None => false,
+                },
+                Ok(Game::Ptcg) => self.requeue_ptcg(&url, identifier).await?,
+                Ok(Game::PtcgJp) => self.requeue_ptcg_jp(&url, identifier).await?,
+                Ok(Game::Yugioh) => self.requeue_yugioh(&url).await?,
+                Err(_) => false,
+            };
+            if requeued {
+                info!("{game}: requeued {url}");
+                self.repository.scrape_errors().resolve(&ids).await?;
+            } else {
+                warn!("{game}: cannot requeue {url}");
+                skipped += 1;
+            }
+        }
+        if skipped > 0 {
+            return Err(Error::NotRequeued(skipped));
+        }
+        Ok(())
+    }
+    // https://asia.pokemon-card.com/tw/card-search/detail/{code}/ with expansion_code|code
+    async fn requeue_ptcg(&self, url: &str, identifier: Option<&str>) -> Result<bool, Error> {
+        let Some((expansion_code, code)) = identifier.and_then(|i| i.split_once('|')) else {
+            return Ok(false);
+        };
+        let Some(region) = url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.path_segments()?.next().and_then(PtcgRegion::from_path))
+        else {
+            return Ok(false);
+        };
+        self.ptcg
+            .for_region(region)
+            .update_printing(code, expansion_code)
+            .await?;
+        Ok(true)
+    }
+    async fn requeue_ptcg_jp(&self, url: &str, identifier: Option<&str>) -> Result<bool, Error> {
+        let Some(card_ref) = identifier.and_then(|i| TcgCollectorCardRef::from_identifier(url, i))
+        else {
+            return Ok(false);
+        };
+        let card = self.ptcg_jp.scraper.fetch_card_html(card_ref).await?;
+        self.repository.save_tcg_collector(vec![card]).await?;
+        Ok(true)
+    }
+    // the url is the printing page of one database, request_locale says which
+    async fn requeue_yugioh(&self, url: &str) -> Result<bool, Error> {
+        let Some(locale) = query_param(url, "request_locale").and_then(|l| l.parse().ok()) else {
+            return Ok(false);
+        };
+        let mut scraper = self.yugioh.clone();
+        scraper.locale = locale;
+        let detail = scraper.fetch_printing_detail(url).await?;
+        CardStore::<YugiohScraper>::save_card(&self.repository, detail, None).await?;
+        Ok(true)
+    }
+}
+
+fn query_param(url: &str, key: &str) -> Option<String> {
+    url::Url::parse(url)
+        .ok()?
+        .query_pairs()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.into_owned())
+}
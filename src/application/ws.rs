@@ -1,8 +1,13 @@
+use chrono::{DateTime, Utc};
 use futures::TryStreamExt;
 use google_cloud_storage::client::ClientConfig;
 
 use crate::{
-    error::Error, export::export_csv::ExportCsv, repository::Repository, scraper::ws::WsScraper,
+    domain::Game,
+    error::Error,
+    export::export_csv::ExportCsv,
+    repository::Repository,
+    scraper::{client::HttpClient, ws::WsScraper},
 };
 
 use super::GcsDownloader;
@@ -10,6 +15,7 @@ use super::GcsDownloader;
 pub struct Ws {
     pub scraper: WsScraper,
     pub repository: Repository,
+    pub client: HttpClient,
 }
 
 impl Ws {
@@ -19,6 +25,7 @@ impl Ws {
         let client = google_cloud_storage::client::Client::new(config);
         let downloader = &GcsDownloader {
             client,
+            http_client: self.client.clone(),
             bucket: bucket.to_string(),
             base_path: base_path.to_string(),
         };
@@ -32,20 +39,37 @@ impl Ws {
             .await?;
         Ok(())
     }
-    pub async fn scrape(&self) -> Result<(), Error> {
-        let total_pages = self.scraper.get_total_pages().await?;
-        let progress = self.repository.get_ws_progress().await?;
-        for n in progress + 1..=total_pages {
-            let cards = self.scraper.scrape_by_page(n).await?;
-            let cards = cards.into_iter().filter_map(|s| s.ok()).collect();
-            self.repository.save_ws_cards(cards).await?;
-            self.repository.update_ws_progress(n + 1).await?;
+    pub async fn scrape_page(&self, page_no: i32) -> Result<(), Error> {
+        let mut cards = vec![];
+        for card in self.scraper.scrape_by_page(page_no).await? {
+            match card {
+                Ok(card) => cards.push(card),
+                Err(failure) => {
+                    self.repository
+                        .scrape_errors()
+                        .record(Game::Ws, &failure)
+                        .await?
+                }
+            }
         }
+        self.repository.save_ws_cards(cards).await?;
         Ok(())
     }
 
-    pub async fn export_csv<W: std::io::Write>(&self, w: W) -> Result<(), Error> {
+    pub async fn export_csv<W: std::io::Write>(
+        &self,
+        as_of: Option<DateTime<Utc>>,
+        w: W,
+    ) -> Result<(), Error> {
         let mut wtr = csv::Writer::from_writer(w);
+        if let Some(at) = as_of {
+            for card in self.repository.history().ws_cards_as_of(at).await? {
+                let p: ExportCsv = card.into();
+                wtr.serialize(p)?;
+            }
+            wtr.flush()?;
+            return Ok(());
+        }
         let mut s = self.repository.get_ws_cards();
         while let Some(card) = s.try_next().await? {
             let p: ExportCsv = card.into();
@@ -1,50 +1,88 @@
+use chrono::{DateTime, Utc};
+use tracing::{info, warn};
+
+use super::ImageStore;
 use crate::{
-    export::export_csv::ExportCsv, repository::Repository, scraper::yugioh::YugiohScraper,
+    domain::YugiohLocale,
+    error::Error,
+    export::export_csv::ExportCsv,
+    repository::Repository,
+    scraper::{client::HttpClient, yugioh::YugiohScraper},
 };
 
 pub struct Yugioh {
     pub scraper: YugiohScraper,
     pub repository: Repository,
+    pub client: HttpClient,
 }
 
 impl Yugioh {
-    pub async fn build_yugioh_expansion_link(&self) {
-        let expansion_links = self.scraper.fetch_expansion_link().await.unwrap();
-        for link in expansion_links {
-            self.repository.upsert_yugioh_expansion_link(&link).await;
-        }
-    }
-    pub async fn build_yugioh_printing_link(&self) -> Option<()> {
-        let link = self.repository.get_yugioh_expansion_link().await?;
-        let url = format!(
-            "https://www.db.yugioh-card.com{}&request_locale=ja",
-            link.url
+    pub async fn update_forbidden_limited(&self) -> Result<(), Error> {
+        let list = self.scraper.fetch_forbidden_limited().await?;
+        info!(
+            "forbidden/limited list of {}: {} cards",
+            list.effective_date,
+            list.cards.len()
         );
-        let printing_links = self.scraper.fetch_printing_link(&url).await.unwrap();
-        for link in printing_links {
-            self.repository.upsert_yugioh_printing_link(&link).await;
-        }
-        link.done().await;
-        Some(())
+        self.repository.save_yugioh_forbidden_limited(&list).await?;
+        Ok(())
     }
-    pub async fn build_yugioh_printing_detail(&self) -> Option<()> {
-        let link = self.repository.get_yugioh_printing_link().await?;
-        let url = format!(
-            "https://www.db.yugioh-card.com{}&request_locale=ja",
-            link.url
-        );
-        let printings = self.scraper.fetch_printing_detail(&url).await.unwrap();
-        for printing in printings {
+    // images of cards scraped from the ja database not stored yet, to a GCS bucket when given or ./images/
+    pub async fn download_images(
+        &self,
+        bucket: Option<&str>,
+        base_path: &str,
+    ) -> Result<(), Error> {
+        let store = ImageStore::new(&self.client, bucket, base_path).await;
+        let mut failed = 0;
+        for (card_id, img_src) in self.repository.get_yugioh_images_to_download().await? {
+            let image = match self.save_image(&store, &card_id, &img_src).await {
+                Ok(image) => image,
+                Err(err) => {
+                    warn!("image of {card_id} from {img_src}: {err}");
+                    failed += 1;
+                    continue;
+                }
+            };
             self.repository
-                .upsert_yugioh_printing_detail(printing)
-                .await
+                .set_yugioh_card_image(&card_id, &image)
+                .await?;
+        }
+        if failed > 0 {
+            // their image stays NULL, so the next run tries them again
+            warn!("{failed} images could not be stored");
         }
-        link.done().await;
-        Some(())
+        Ok(())
+    }
+    async fn save_image(
+        &self,
+        store: &ImageStore,
+        card_id: &str,
+        img_src: &str,
+    ) -> Result<String, Error> {
+        let url = url::Url::parse(img_src)?;
+        store
+            .save(&self.client, url, &format!("yugioh/{card_id}.jpg"))
+            .await
     }
-    pub async fn export_yugioh_printing_detail<W: std::io::Write>(&self, w: W) {
+    // one row per printing and locale, `locale` narrows it down to one database
+    pub async fn export_yugioh_printing_detail<W: std::io::Write>(
+        &self,
+        as_of: Option<DateTime<Utc>>,
+        locale: Option<YugiohLocale>,
+        w: W,
+    ) {
+        let printings = match as_of {
+            Some(at) => {
+                self.repository
+                    .history()
+                    .yugioh_printing_as_of(at, locale)
+                    .await
+            }
+            None => self.repository.get_yugioh_printing(locale).await,
+        };
         let mut wtr = csv::Writer::from_writer(w);
-        for printing in self.repository.get_yugioh_printing().await.unwrap() {
+        for printing in printings.unwrap() {
             let p: ExportCsv = printing.into();
             wtr.serialize(p).unwrap();
         }
@@ -1,5 +1,5 @@
 use derive_builder::Builder;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::{
     postgres::{PgHasArrayType, PgTypeInfo},
     types::time::OffsetDateTime,
@@ -7,6 +7,279 @@ use sqlx::{
 use strum::EnumString;
 use time::macros::format_description;
 
+#[derive(
+    Debug, Clone, Copy, PartialEq, Deserialize, clap::ValueEnum, strum::Display, EnumString,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Game {
+    Ptcg,
+    PtcgJp,
+    Yugioh,
+    Ws,
+    OnePiece,
+}
+
+// request_locale of the Konami card database, each region has its own printings
+#[derive(
+    Debug, Clone, Copy, PartialEq, Default, Deserialize, clap::ValueEnum, strum::Display, EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum YugiohLocale {
+    #[default]
+    Ja,
+    En,
+    Ko,
+    // Asian-English
+    Ae,
+    // Traditional Chinese (zh-TW)
+    Cn,
+}
+
+impl YugiohLocale {
+    // non-default locales are part of the scrape_runs command, so each is compared with its own runs
+    pub fn qualify(&self, command: String) -> String {
+        match self {
+            YugiohLocale::Ja => command,
+            locale => format!("{command} --locale {locale}"),
+        }
+    }
+    pub fn language(&self) -> &'static str {
+        match self {
+            YugiohLocale::Ja => "JP",
+            YugiohLocale::En => "EN",
+            YugiohLocale::Ko => "KR",
+            YugiohLocale::Ae => "AE",
+            YugiohLocale::Cn => "TC",
+        }
+    }
+}
+
+// catalogue of asia.pokemon-card.com, all served with the same markup
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Default,
+    Serialize,
+    Deserialize,
+    clap::ValueEnum,
+    strum::Display,
+    EnumString,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum PtcgRegion {
+    #[default]
+    Tw,
+    Hk,
+    Th,
+    Id,
+    // English catalogue for Singapore, Malaysia and the Philippines
+    En,
+}
+
+impl PtcgRegion {
+    // like YugiohLocale::qualify, each region is compared with its own runs
+    pub fn qualify(&self, command: String) -> String {
+        match self {
+            PtcgRegion::Tw => command,
+            region => format!("{command} --region {region}"),
+        }
+    }
+    // first path segment of the site, also the prefix of card image names
+    pub fn path(&self) -> &'static str {
+        match self {
+            PtcgRegion::Tw => "tw",
+            PtcgRegion::Hk => "hk",
+            PtcgRegion::Th => "th",
+            PtcgRegion::Id => "id",
+            PtcgRegion::En => "sg",
+        }
+    }
+    pub fn from_path(path: &str) -> Option<Self> {
+        [
+            PtcgRegion::Tw,
+            PtcgRegion::Hk,
+            PtcgRegion::Th,
+            PtcgRegion::Id,
+            PtcgRegion::En,
+        ]
+        .into_iter()
+        .find(|region| region.path() == path)
+    }
+    // language the cards are printed in
+    pub fn language(&self) -> &'static str {
+        match self {
+            PtcgRegion::Tw | PtcgRegion::Hk => "TC",
+            PtcgRegion::Th => "TH",
+            PtcgRegion::Id => "ID",
+            PtcgRegion::En => "EN",
+        }
+    }
+    // market the cards are sold in
+    pub fn p_language(&self) -> &'static str {
+        match self {
+            PtcgRegion::Tw => "TW",
+            PtcgRegion::Hk => "HK",
+            PtcgRegion::Th => "TH",
+            PtcgRegion::Id => "ID",
+            PtcgRegion::En => "SG",
+        }
+    }
+}
+
+// OCG rarity codes as printed in the `.icon p` of a printing row
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, PartialEq, EnumString, strum::Display)]
+pub enum YugiohRarity {
+    N,
+    NR,
+    R,
+    SR,
+    UR,
+    // Ultimate (relief)
+    UL,
+    SE,
+    HR,
+    CR,
+    GR,
+    GSE,
+    PSE,
+    EXSE,
+    QCSE,
+    #[strum(serialize = "20SE")]
+    TwentiethSE,
+    NP,
+    P,
+    UPR,
+    KC,
+    M,
+    // kept as printed, so a new code is not lost before it gets a variant
+    #[strum(default)]
+    Unknown(String),
+}
+
+impl YugiohRarity {
+    pub fn from_code(code: &str) -> Self {
+        code.parse()
+            .unwrap_or_else(|_| YugiohRarity::Unknown(code.to_string()))
+    }
+}
+
+// Flags decoded from the remark of a printing row, in Japanese or English
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum YugiohRemark {
+    NewArtwork,
+    Reprint,
+    FirstPrinting,
+}
+
+impl YugiohRemark {
+    pub fn parse(remark: &str) -> Vec<Self> {
+        let remark = remark.to_lowercase();
+        let mut flags = vec![];
+        if remark.contains("新規イラスト") || remark.contains("new artwork") {
+            flags.push(YugiohRemark::NewArtwork);
+        }
+        if remark.contains("再録") || remark.contains("reprint") {
+            flags.push(YugiohRemark::Reprint);
+        }
+        if remark.contains("初収録") || remark.contains("new card") {
+            flags.push(YugiohRemark::FirstPrinting);
+        }
+        flags
+    }
+}
+
+// status of a card on the OCG Forbidden/Limited list in effect
+#[derive(Debug, Clone, Copy, PartialEq, Default, strum::Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum YugiohLegality {
+    Forbidden,
+    Limited,
+    SemiLimited,
+    #[default]
+    Unlimited,
+}
+
+// section of a PTCG card's text box a skill was listed under
+#[derive(Debug, Clone, Copy, PartialEq, strum::Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum PtcgSkillKind {
+    Ability,
+    Attack,
+    // trainer and energy card effect text
+    Effect,
+    Rule,
+}
+
+// Card tables whose row versions are kept in card_history
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, strum::Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum HistoryTable {
+    PokemonTrainerPrinting,
+    YugiohPrintingDetail,
+    OnePiece,
+    WsCards,
+    TcgCollector,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, strum::Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum JobQueue {
+    PtcgFetchable,
+    TcgCollectorLink,
+    WsPage,
+    OnePieceSeries,
+}
+
+// Unit of work handed out by the scrape_jobs queue
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "queue", rename_all = "snake_case")]
+pub enum JobPayload {
+    PtcgFetchable {
+        code: String,
+        expansion_code: String,
+        #[serde(default)]
+        region: PtcgRegion,
+    },
+    TcgCollectorLink {
+        url: String,
+    },
+    WsPage {
+        page: i32,
+    },
+    OnePieceSeries {
+        series: String,
+    },
+}
+
+impl JobQueue {
+    pub fn game(&self) -> Game {
+        match self {
+            JobQueue::PtcgFetchable => Game::Ptcg,
+            JobQueue::TcgCollectorLink => Game::PtcgJp,
+            JobQueue::WsPage => Game::Ws,
+            JobQueue::OnePieceSeries => Game::OnePiece,
+        }
+    }
+}
+
+impl JobPayload {
+    pub fn queue(&self) -> JobQueue {
+        match self {
+            JobPayload::PtcgFetchable { .. } => JobQueue::PtcgFetchable,
+            JobPayload::TcgCollectorLink { .. } => JobQueue::TcgCollectorLink,
+            JobPayload::WsPage { .. } => JobQueue::WsPage,
+            JobPayload::OnePieceSeries { .. } => JobQueue::OnePieceSeries,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LastFetchedAt {
     pub inner: OffsetDateTime,
@@ -42,6 +315,23 @@ pub struct PokemonCard {
     pub rarity: Option<String>,
     pub last_fetched_at: LastFetchedAt,
     pub remark: Option<String>,
+    pub region: PtcgRegion,
+    pub attributes: PtcgPrintingAttributes,
+}
+
+#[derive(Default, Debug, Clone, Serialize)]
+pub struct PtcgPrintingAttributes {
+    pub kind: Option<String>,
+    pub evolve_marker: Option<String>,
+    pub img_src: Option<String>,
+    pub hp: Option<String>,
+    pub weak_point: Option<String>,
+    pub resist: Option<String>,
+    pub escape: Option<String>,
+    pub expansion_symbol: Option<String>,
+    pub energy: Option<String>,
+    pub artist: Option<String>,
+    pub regulation_mark: Option<String>,
 }
 
 #[allow(clippy::upper_case_acronyms)]
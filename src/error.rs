@@ -1,6 +1,10 @@
 use std::num::ParseIntError;
 
-use crate::{repository::RepositoryError, scraper::scraper_error::ScraperError};
+use crate::{
+    repository::RepositoryError,
+    scraper::{scraper_error::ScraperError, yugioh},
+    strategy::StrategyError,
+};
 
 #[derive(thiserror::Error, Debug)]
 pub(crate) enum Error {
@@ -22,9 +26,71 @@ pub(crate) enum Error {
     ParseInt(#[from] ParseIntError),
     #[error("csv error {0}")]
     Csv(#[from] csv::Error),
+    #[error("yugioh scraper error {0}")]
+    Yugioh(#[from] yugioh::Error),
+    #[error("{0} pages could not be requeued and stay unresolved")]
+    NotRequeued(usize),
+    #[error("strategy of {0}: {1}")]
+    Strategy(String, StrategyError),
+    #[error("{0} problems in the strategy file, nothing imported")]
+    InvalidStrategies(usize),
+    #[error("--version needs the expansions to apply")]
+    VersionWithoutExpansion,
+    #[error("no file name in {0}")]
+    NoFileName(String),
 }
 
-#[derive(Debug)]
+#[derive(thiserror::Error, Debug)]
 pub enum ErrorCode {
+    #[error("rarity not exists")]
     RarityNotExists,
+    #[error(transparent)]
+    Scraper(#[from] ScraperError),
+    #[error("{0}")]
+    Detail(String),
+    #[error("image: {0}")]
+    Image(String),
+}
+
+impl ErrorCode {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ErrorCode::RarityNotExists => "rarity_not_exists",
+            ErrorCode::Scraper(ScraperError::MissingElement { .. }) => "missing_element",
+            ErrorCode::Scraper(ScraperError::MissingAttribute { .. }) => "missing_attribute",
+            ErrorCode::Scraper(ScraperError::InvalidValue { .. }) => "invalid_value",
+            ErrorCode::Scraper(_) => "scraper",
+            ErrorCode::Detail(_) => "detail",
+            ErrorCode::Image(_) => "image",
+        }
+    }
+}
+
+const SNIPPET_LEN: usize = 2000;
+
+// One card that could not be scraped, kept in the scrape_errors ledger
+#[derive(Debug)]
+pub struct CardFailure {
+    pub url: String,
+    pub card_identifier: Option<String>,
+    pub kind: &'static str,
+    pub message: String,
+    pub snippet: Option<String>,
+}
+
+impl CardFailure {
+    pub fn new(
+        url: &str,
+        card_identifier: Option<String>,
+        code: ErrorCode,
+        snippet: Option<String>,
+    ) -> Self {
+        Self {
+            url: url.to_string(),
+            card_identifier,
+            kind: code.kind(),
+            message: code.to_string(),
+            snippet: snippet.map(|s| s.chars().take(SNIPPET_LEN).collect()),
+        }
+    }
 }
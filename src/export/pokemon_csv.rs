@@ -1,4 +1,7 @@
-use crate::{domain::PokemonCard, export::export_csv::ExportCsv};
+use crate::{
+    domain::{PokemonCard, PtcgRegion},
+    export::export_csv::ExportCsv,
+};
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -13,10 +16,12 @@ impl From<PokemonCard> for ExportCsv {
             rarity: value.rarity,
             material: None,
             release_year: None,
-            language: Some(String::from("ja")),
-            card_name_english: None,
-            card_name_chinese: None,
-            card_name_japanese: Some(value.name),
+            language: Some(value.region.language().to_string()),
+            card_name_english: (value.region == PtcgRegion::En).then(|| value.name.clone()),
+            card_name_chinese: matches!(value.region, PtcgRegion::Tw | PtcgRegion::Hk)
+                .then_some(value.name),
+            // no asia.pokemon-card.com catalogue is Japanese, th and id names have no column
+            card_name_japanese: None,
             card_number: value.number,
             image: Some(format!("{}.jpg", value.id)),
             value: value.sale_price.map(|p| p.to_string()),
@@ -31,9 +36,9 @@ impl From<PokemonCard> for ExportCsv {
             remark7: None,
             remark8: None,
             remark9: value.remark,
-            remark10: None,
+            remark10: serde_json::to_string(&value.attributes).ok(),
             enable: None,
-            p_language: None,
+            p_language: Some(value.region.p_language().to_string()),
             id: None,
         }
     }
@@ -50,6 +55,40 @@ fn sanitize(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::domain::{LastFetchedAt, PtcgPrintingAttributes};
+
+    fn card(region: PtcgRegion) -> PokemonCard {
+        PokemonCard {
+            id: "tw00001".to_string(),
+            set_id: "sv5a".to_string(),
+            set_name: "sv5a".to_string(),
+            set_ref: "sv5a".to_string(),
+            name: "name".to_string(),
+            number: None,
+            sale_price: None,
+            rarity: None,
+            last_fetched_at: LastFetchedAt {
+                inner: time::OffsetDateTime::UNIX_EPOCH,
+            },
+            remark: None,
+            region,
+            attributes: PtcgPrintingAttributes::default(),
+        }
+    }
+    #[test]
+    fn name_column_follows_region() {
+        let tw: ExportCsv = card(PtcgRegion::Tw).into();
+        assert_eq!(tw.card_name_chinese.as_deref(), Some("name"));
+        assert_eq!(tw.card_name_english, None);
+        assert_eq!(tw.card_name_japanese, None);
+        let en: ExportCsv = card(PtcgRegion::En).into();
+        assert_eq!(en.card_name_english.as_deref(), Some("name"));
+        assert_eq!(en.card_name_chinese, None);
+        let th: ExportCsv = card(PtcgRegion::Th).into();
+        assert_eq!(th.card_name_english, None);
+        assert_eq!(th.card_name_chinese, None);
+        assert_eq!(th.card_name_japanese, None);
+    }
     #[test]
     fn sanitize_x_kind() {
         let result = sanitize("バトル強化デッキ 3種");
@@ -1,9 +1,18 @@
-use crate::{export::export_csv::ExportCsv, scraper::yugioh::YugiohPrinting};
+use crate::{domain::YugiohLocale, export::export_csv::ExportCsv, scraper::yugioh::YugiohPrinting};
 use time::macros::format_description;
 
 impl From<YugiohPrinting> for ExportCsv {
     fn from(value: YugiohPrinting) -> Self {
         let now = time::OffsetDateTime::now_utc();
+        let locale: YugiohLocale = value.locale.parse().unwrap_or_default();
+        let language = locale.language();
+        let remark_flags = value
+            .remark_flags()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(",");
+        let card_name_chinese = (locale == YugiohLocale::Cn).then_some(value.name);
         let format = format_description!("[day]/[month]/[year] [hour]:[minute]");
         Self {
             product_id: None,
@@ -11,25 +20,25 @@ impl From<YugiohPrinting> for ExportCsv {
             set: Some(value.expansion_name),
             edition: None,
             series: None,
-            rarity: Some(value.rarity),
+            rarity: Some(value.rarity.to_string()),
             material: None,
             release_year: Some(value.release_date.split_once('-').unwrap().0.to_owned()),
-            language: Some(String::from("JP")),
+            language: Some(language.to_string()),
             card_name_english: Some(value.name_en),
-            card_name_chinese: None,
+            card_name_chinese,
             card_name_japanese: Some(value.name_jp),
-            card_number: Some(value.number),
-            image: None,
+            card_number: value.number,
+            image: value.image,
             value: None,
-            reference: Some(value.r#ref),
+            reference: value.r#ref,
             remark: None,
             remark1: Some(now.unix_timestamp().to_string()),
             remark2: Some(now.format(format).unwrap()),
             remark3: None,
             remark4: None,
             remark5: Some(value.remark),
-            remark6: None,
-            remark7: None,
+            remark6: (!remark_flags.is_empty()).then_some(remark_flags),
+            remark7: Some(value.legality.to_string()),
             remark8: None,
             remark9: None,
             remark10: None,
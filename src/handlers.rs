@@ -8,7 +8,7 @@ use reqwest::{header::CONTENT_TYPE, StatusCode};
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres};
 
-use crate::application::ptcg::Ptcg;
+use crate::{application::ptcg::Ptcg, domain::PtcgRegion};
 
 #[derive(Clone)]
 pub struct MyState {
@@ -106,6 +106,8 @@ pub async fn pokemon() -> Result<Markup, Error> {
 #[derive(Deserialize)]
 pub struct ListQuery {
     code: String,
+    #[serde(default)]
+    region: PtcgRegion,
 }
 pub async fn list(query: Query<ListQuery>, state: State<MyState>) -> Result<Markup, Error> {
     let cards = sqlx::query!(
@@ -115,25 +117,28 @@ pub async fn list(query: Query<ListQuery>, state: State<MyState>) -> Result<Mark
             COALESCE(ptp.number, wiki.number) AS "number!",
             COALESCE(ptp.expansion_code, wiki.exp_code) "exp_code!",
             COALESCE(ptp.rarity, wiki.rarity::TEXT) rarity,
-            ptp.code as "code?"
+            ptp.code as "code?",
+            ptp.img_src
         FROM pokemon_trainer_printing ptp
         FULL JOIN pokewiki wiki
             ON LOWER(wiki.exp_code) = LOWER(ptp.expansion_code)
             AND wiki.name = ptp.name
             AND wiki.number = ptp.number
         WHERE
-            LOWER(ptp.expansion_code) = LOWER($1) OR LOWER(wiki.exp_code) = LOWER($1)
+            (LOWER(ptp.expansion_code) = LOWER($1) OR LOWER(wiki.exp_code) = LOWER($1))
+            AND COALESCE(ptp.region, 'tw') = $2
         ORDER BY "number!"
         "#,
-        query.code
+        query.code,
+        query.region.to_string()
     )
     .fetch_all(&state.pool)
     .await?;
     let markup = html! {
         h1 { (query.code) }
         @for card in cards {
-            div.flex hx-get={ (format!("/modal?name={}&number={}&exp_code={}", card.name, card.number, card.exp_code)) } hx-target="body" hx-swap="beforeend" {
-                div.width-ten { img.table_img src={(format!("https://asia.pokemon-card.com/tw/card-img/tw{:08}.png", card.code.unwrap_or("0".to_string()).parse::<i32>().unwrap()))}; }
+            div.flex hx-get={ (format!("/modal?name={}&number={}&exp_code={}&region={}", card.name, card.number, card.exp_code, query.region)) } hx-target="body" hx-swap="beforeend" {
+                div.width-ten { img.table_img src={(card_image(card.img_src, card.code, query.region))}; }
                 div.quater { (card.name) }
                 div.quater { (card.number) }
                 div.width-ten { (card.rarity.unwrap_or("Unknown".to_string())) }
@@ -145,14 +150,14 @@ pub async fn list(query: Query<ListQuery>, state: State<MyState>) -> Result<Mark
 }
 pub async fn exp_list(state: State<MyState>) -> Result<Markup, Error> {
     let exps =
-        sqlx::query!("SELECT code, name FROM pokemon_trainer_expansion ORDER BY release_date DESC")
+        sqlx::query!("SELECT code, name, region FROM pokemon_trainer_expansion ORDER BY region, release_date DESC")
             .fetch_all(&state.pool)
             .await?;
     let markup = html! {
         .pad {
             @for exp in exps {
-                a.green href="#" _="on click take .selected from a.green for the event's target" hx-get={ (format!("/list?code={}", exp.code)) } hx-target="#list" {
-                    (format!("{:<5}:{}",exp.code, exp.name))
+                a.green href="#" _="on click take .selected from a.green for the event's target" hx-get={ (format!("/list?code={}&region={}", exp.code, exp.region)) } hx-target="#list" {
+                    (format!("{:<3}{:<5}:{}", exp.region, exp.code, exp.name))
                 }
                 br;
             }
@@ -166,6 +171,8 @@ pub struct ModalQuery {
     name: String,
     number: String,
     exp_code: String,
+    #[serde(default)]
+    region: PtcgRegion,
 }
 
 pub async fn modal(state: State<MyState>, query: Query<ModalQuery>) -> Result<Markup, Error> {
@@ -175,7 +182,18 @@ pub async fn modal(state: State<MyState>, query: Query<ModalQuery>) -> Result<Ma
             COALESCE(ptp.name, wiki.name) AS "name!",
             COALESCE(ptp.number, wiki.number) AS "number!",
             COALESCE(ptp.expansion_code, wiki.exp_code) "exp_code!",
-            COALESCE(ptp.rarity, wiki.rarity::TEXT) rarity
+            COALESCE(ptp.rarity, wiki.rarity::TEXT) rarity,
+            ptp.code as "code?",
+            ptp.kind as "kind?",
+            ptp.evolve_marker,
+            ptp.img_src,
+            ptp.hp,
+            ptp.weak_point,
+            ptp.resist,
+            ptp.escape,
+            ptp.expansion_symbol,
+            ptp.energy,
+            ptp.artist
         FROM pokemon_trainer_printing ptp
         FULL JOIN pokewiki wiki
             ON LOWER(wiki.exp_code) = LOWER(ptp.expansion_code)
@@ -187,10 +205,13 @@ pub async fn modal(state: State<MyState>, query: Query<ModalQuery>) -> Result<Ma
             (ptp.name = $2 OR wiki.name = $2)
             AND
             (ptp.number = $3 OR wiki.number = $3)
+            AND
+            COALESCE(ptp.region, 'tw') = $4
         "#,
         query.exp_code,
         query.name,
-        query.number
+        query.number,
+        query.region.to_string()
     )
     .fetch_one(&state.pool)
     .await?;
@@ -201,8 +222,18 @@ pub async fn modal(state: State<MyState>, query: Query<ModalQuery>) -> Result<Ma
             .modal-underlay _="on click trigger closeModal" {}
             .modal-content {
                 h1 { "hihi" }
+                img.table_img src={(card_image(card.img_src, card.code, query.region))};
                 div { (card.name) }
                 div { (card.rarity.unwrap_or("Unknown".to_string())) }
+                @if let Some(kind) = card.kind { div { (kind) } }
+                @if let Some(evolve_marker) = card.evolve_marker { div { (evolve_marker) } }
+                @if let Some(hp) = card.hp { div { "HP " (hp) } }
+                @if let Some(energy) = card.energy { div { img src={(energy)}; } }
+                @if let Some(weak_point) = card.weak_point { div { "weakness " (weak_point) } }
+                @if let Some(resist) = card.resist { div { "resistance " (resist) } }
+                @if let Some(escape) = card.escape { div { "retreat " (escape) } }
+                @if let Some(expansion_symbol) = card.expansion_symbol { div { (expansion_symbol) } }
+                @if let Some(artist) = card.artist { div { "illus. " (artist) } }
                 div {
                     input value={(n)};
                     "/"
@@ -216,6 +247,14 @@ pub async fn modal(state: State<MyState>, query: Query<ModalQuery>) -> Result<Ma
     })
 }
 
+fn card_image(img_src: Option<String>, code: Option<String>, region: PtcgRegion) -> String {
+    img_src.unwrap_or_else(|| {
+        let code = code.and_then(|c| c.parse::<i32>().ok()).unwrap_or(0);
+        let path = region.path();
+        format!("https://asia.pokemon-card.com/{path}/card-img/{path}{code:08}.png")
+    })
+}
+
 pub async fn stylesheets() -> impl IntoResponse {
     let css = include_str!("stylesheets.css");
     ([(CONTENT_TYPE, "text/css")], css)
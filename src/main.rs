@@ -4,19 +4,26 @@ mod error;
 mod export;
 mod handlers;
 mod repository;
+mod rotation;
+mod schedule;
 mod scraper;
 mod strategy;
 
 use application::Application;
 use axum::{routing::get, Router};
+use chrono::{DateTime, NaiveDate, Utc};
 use clap::{Parser, Subcommand};
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Result};
+use domain::{Game, HistoryTable, JobQueue, PtcgRegion, YugiohLocale};
 use export::export_csv::ExportCsv;
 use handlers::exp_list;
 use meilisearch_sdk::client::Client;
+use rotation::{PtcgFormat, Rotation};
+use schedule::ScheduleEntry;
+use scraper::client::FetchMode;
 use serde::Deserialize;
 use sqlx::PgPool;
-use std::{thread::sleep, time::Duration};
+use std::{path::PathBuf, time::Duration};
 use strategy::Source;
 use tracing::info;
 
@@ -26,14 +33,42 @@ use crate::handlers::{list, modal, pokemon, prepare, root, search, stylesheets,
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Parse pages from the page archive instead of fetching them
+    #[arg(long, global = true)]
+    replay: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
-    #[command(subcommand)]
-    Ptcg(PtcgCommands),
-    #[command(subcommand)]
-    Yugioh(YugiohCommands),
+    /// Run the expansion -> card -> detail pipeline of a game
+    Scrape {
+        game: Game,
+        #[arg(long)]
+        images: bool,
+        /// Store the images in this GCS bucket instead of ./images/
+        #[arg(long, requires = "images")]
+        bucket: Option<String>,
+        /// Konami database of the yugioh pipeline
+        #[arg(long, value_enum, default_value_t)]
+        locale: YugiohLocale,
+        /// asia.pokemon-card.com catalogue of the ptcg pipeline
+        #[arg(long, value_enum, default_value_t)]
+        region: PtcgRegion,
+    },
+    Ptcg {
+        /// asia.pokemon-card.com catalogue to read, each region has its own printings
+        #[arg(long, value_enum, default_value_t)]
+        region: PtcgRegion,
+        #[command(subcommand)]
+        command: PtcgCommands,
+    },
+    Yugioh {
+        /// Konami database to read, each region has its own printings
+        #[arg(long, value_enum, default_value_t)]
+        locale: YugiohLocale,
+        #[command(subcommand)]
+        command: YugiohCommands,
+    },
     #[command(subcommand)]
     Ws(WsCommands),
     #[command(subcommand)]
@@ -42,6 +77,87 @@ enum Commands {
     PtcgJp(PtcgJpCommands),
     #[command(subcommand)]
     Serve(ServeCommands),
+    #[command(subcommand)]
+    Errors(ErrorsCommands),
+    #[command(subcommand)]
+    Jobs(JobsCommands),
+    #[command(subcommand)]
+    Runs(RunsCommands),
+    #[command(subcommand)]
+    History(HistoryCommands),
+    /// Run the scrapes of a schedule file until SIGTERM
+    Daemon {
+        #[arg(long, default_value = "schedule.json")]
+        schedule: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum HistoryCommands {
+    /// Print every version of a card as JSON lines
+    Show {
+        table: HistoryTable,
+        card_key: String,
+    },
+    /// Print the versions of a table valid at a time as JSON lines
+    AsOf {
+        table: HistoryTable,
+        at: DateTime<Utc>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RunsCommands {
+    /// Print the latest scrape runs as CSV
+    List {
+        #[arg(long)]
+        game: Option<Game>,
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+    /// Print one scrape run
+    Show { id: i32 },
+    /// Print the cards a run added, removed or changed as JSON lines
+    Changelog {
+        id: i32,
+        /// Print a human readable summary instead
+        #[arg(long)]
+        summary: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum JobsCommands {
+    /// Enqueue every known unit of work of a queue
+    Seed { queue: JobQueue },
+    /// Claim and run jobs until the queue is empty
+    Work {
+        queue: JobQueue,
+        /// Seconds before a claimed job becomes visible to other workers again
+        #[arg(long, default_value_t = 300)]
+        lease_secs: u64,
+    },
+    /// Print job counts per queue and status as CSV
+    Stats,
+    /// Move dead jobs back to pending
+    RetryDead { queue: JobQueue },
+}
+
+#[derive(Subcommand)]
+enum ErrorsCommands {
+    /// Print recorded scrape failures as CSV
+    List {
+        #[arg(long)]
+        game: Option<Game>,
+        /// Include failures that were already resolved
+        #[arg(long)]
+        all: bool,
+    },
+    /// Re-scrape the pages of unresolved failures
+    Requeue {
+        #[arg(long)]
+        game: Option<Game>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -51,9 +167,7 @@ enum ServeCommands {
 
 #[derive(Subcommand)]
 enum PtcgJpCommands {
-    Exp,
     Card,
-    Tc,
     Extra,
     Rarity,
 }
@@ -61,35 +175,179 @@ enum PtcgJpCommands {
 #[derive(Subcommand)]
 enum PtcgCommands {
     Prepare,
-    Run,
-    ExportCsv,
-    Strategy,
+    ExportCsv {
+        /// Export the printings of every region instead of only --region
+        #[arg(long)]
+        all_regions: bool,
+    },
+    /// Run the strategy CSV (exp,name,strategy) read from stdin, or manage the stored strategies
+    Strategy {
+        /// Validate the rows and print the planned actions without fetching anything
+        #[arg(long)]
+        dry_run: bool,
+        #[command(subcommand)]
+        command: Option<StrategyCommands>,
+    },
+    /// Print the printings leaving Standard at the next announced rotation as CSV
+    Legality {
+        /// Check legality on this date instead of today, e.g. 2026-01-23
+        #[arg(long)]
+        on: Option<NaiveDate>,
+        /// Print every printing with its legality, not only the rotating ones
+        #[arg(long)]
+        all: bool,
+        #[arg(long)]
+        all_regions: bool,
+    },
+    /// Record that from effective_date on, marks from oldest_mark up are legal in format
+    Rotation {
+        format: PtcgFormat,
+        effective_date: NaiveDate,
+        oldest_mark: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum StrategyCommands {
+    /// Store the strategy CSV from stdin, adding a version for every changed expansion
+    Import {
+        /// Defaults to $USER
+        #[arg(long)]
+        author: Option<String>,
+    },
+    /// Print the latest stored strategies in the CSV format import reads
+    Export { exp: Option<String> },
+    /// Show what changed between two versions, by default the latest and the one before
+    Diff {
+        exp: String,
+        #[arg(long)]
+        from: Option<i32>,
+        #[arg(long)]
+        to: Option<i32>,
+    },
+    /// Run the latest stored strategy of the expansions, of every expansion when none is given
+    Apply {
+        exps: Vec<String>,
+        /// Run this version instead of the latest
+        #[arg(long, requires = "exps")]
+        version: Option<i32>,
+    },
+    /// List the versions of an expansion's strategy and the rows each one last wrote
+    History { exp: String },
 }
 
 #[derive(Subcommand)]
 enum WsCommands {
-    Scrape,
     DownloadImages,
-    ExportCsv,
+    ExportCsv {
+        /// Export the cards as they were at this time, e.g. 2026-10-01T00:00:00Z
+        #[arg(long)]
+        as_of: Option<DateTime<Utc>>,
+    },
 }
 
 #[derive(Subcommand)]
 enum YugiohCommands {
-    BuildExpLink,
-    BuildPriLink,
-    BuildDetail,
-    ExportCsv,
+    /// Save the OCG Forbidden/Limited list in effect
+    ForbiddenLimited,
+    /// Store the card images not downloaded yet, in ./images/ unless --bucket is given
+    DownloadImages {
+        #[arg(long)]
+        bucket: Option<String>,
+        #[arg(long, default_value = "yugioh_images")]
+        base_path: String,
+    },
+    ExportCsv {
+        /// Export the printings as they were at this time, e.g. 2026-10-01T00:00:00Z
+        #[arg(long)]
+        as_of: Option<DateTime<Utc>>,
+        /// Export the printings of every locale instead of only --locale
+        #[arg(long)]
+        all_locales: bool,
+    },
 }
 
 #[derive(Subcommand)]
 enum OnePieceCommands {
-    Scrape,
     ScrapeProducts,
     DownloadImages,
     ExportCsv,
     ExportProductCsv,
 }
 
+impl Commands {
+    // commands that scrape open a scrape_runs record
+    fn scrape_run(&self) -> Option<(Game, String)> {
+        let run = match self {
+            Commands::Scrape {
+                game: Game::Yugioh,
+                locale,
+                ..
+            } => (Game::Yugioh, locale.qualify("scrape yugioh".to_string())),
+            Commands::Scrape {
+                game: Game::Ptcg,
+                region,
+                ..
+            } => (Game::Ptcg, region.qualify("scrape ptcg".to_string())),
+            Commands::Scrape { game, .. } => (*game, format!("scrape {game}")),
+            Commands::Ptcg { region, command } => {
+                let name = match command {
+                    PtcgCommands::Prepare => "prepare",
+                    PtcgCommands::Strategy {
+                        dry_run: false,
+                        command: None,
+                    } => "strategy",
+                    PtcgCommands::Strategy {
+                        command: Some(StrategyCommands::Apply { .. }),
+                        ..
+                    } => "strategy apply",
+                    PtcgCommands::Strategy { .. }
+                    | PtcgCommands::ExportCsv { .. }
+                    | PtcgCommands::Legality { .. }
+                    | PtcgCommands::Rotation { .. } => return None,
+                };
+                (Game::Ptcg, region.qualify(format!("ptcg {name}")))
+            }
+            Commands::Yugioh { locale, command } => {
+                let name = match command {
+                    YugiohCommands::ForbiddenLimited => "forbidden-limited",
+                    YugiohCommands::DownloadImages { .. } => "download-images",
+                    YugiohCommands::ExportCsv { .. } => return None,
+                };
+                (Game::Yugioh, locale.qualify(format!("yugioh {name}")))
+            }
+            Commands::PtcgJp(command) => {
+                let name = match command {
+                    PtcgJpCommands::Card => "card",
+                    PtcgJpCommands::Extra => "extra",
+                    PtcgJpCommands::Rarity => "rarity",
+                };
+                (Game::PtcgJp, format!("ptcg-jp {name}"))
+            }
+            Commands::Ws(WsCommands::DownloadImages) => {
+                (Game::Ws, "ws download-images".to_string())
+            }
+            Commands::OnePiece(command) => {
+                let name = match command {
+                    OnePieceCommands::ScrapeProducts => "scrape-products",
+                    OnePieceCommands::DownloadImages => "download-images",
+                    OnePieceCommands::ExportCsv => "export-csv",
+                    OnePieceCommands::ExportProductCsv => "export-product-csv",
+                };
+                (Game::OnePiece, format!("one-piece {name}"))
+            }
+            Commands::Jobs(JobsCommands::Work { queue, .. }) => {
+                (queue.game(), format!("jobs work {queue}"))
+            }
+            Commands::Errors(ErrorsCommands::Requeue { game: Some(game) }) => {
+                (*game, "errors requeue".to_string())
+            }
+            _ => return None,
+        };
+        Some(run)
+    }
+}
+
 #[derive(Deserialize, Debug)]
 struct PtcgStrategyRow {
     exp: String,
@@ -103,88 +361,165 @@ async fn main() -> Result<()> {
     color_eyre::install()?;
     let cli = Cli::parse();
     let database_url = std::env::var("DATABASE_URL")?;
-    let application = Application::new(&database_url);
+    let mode = if cli.replay {
+        FetchMode::Replay
+    } else {
+        FetchMode::Live
+    };
+    let application = Application::new(&database_url, mode);
 
-    match &cli.command {
-        Commands::Ptcg(commands) => match commands {
+    let Some((game, command)) = cli.command.scrape_run() else {
+        let result = execute(&application, &cli.command).await;
+        application.close().await;
+        return result;
+    };
+    // taken before the run opens, so a command refused the lock leaves no run behind,
+    // and held until it finishes so a manual run never overlaps the daemon's or another one
+    let Some(lock) = application.try_lock(&command).await? else {
+        return Err(eyre!("{command} is already running"));
+    };
+    let run = application.runs().start(game, &command).await?;
+    let result = execute(&application, &cli.command).await;
+    application.close().await;
+    let error_message = result.as_ref().err().map(|err| err.to_string());
+    application.runs().finish(run, error_message).await?;
+    lock.release().await?;
+    result
+}
+
+async fn execute(application: &Application, command: &Commands) -> Result<()> {
+    match command {
+        Commands::Scrape {
+            game,
+            images,
+            bucket,
+            locale,
+            region,
+        } => {
+            let (_, job) = command.scrape_run().expect("scrape opens a run");
+            application
+                .scrape(*game, &job, *images, bucket.as_deref(), *locale, *region)
+                .await?;
+        }
+        Commands::Ptcg { region, command } => match command {
             PtcgCommands::Prepare => {
-                let pokemon_trainer = application.ptcg();
-                // pokemon_trainer.prepare_ptcg_expansions().await?;
-                // pokemon_trainer.update_ptcg_fetchable().await?;
-                // pokemon_trainer.update_ptcg_printing().await?;
-                pokemon_trainer.update_rarity().await?;
-                // pokemon_trainer.download_all_image().await?;
-            }
-            PtcgCommands::Run => {
-                // let pokemon_trainer = application.ptcg();
+                application.ptcg(*region).update_rarity().await?;
             }
-            PtcgCommands::ExportCsv => {
+            PtcgCommands::ExportCsv { all_regions } => {
                 let mut wtr = csv::Writer::from_writer(std::io::stdout());
-                let pokemon_trainer = application.ptcg();
-                let all_cards = pokemon_trainer.export_pokemon_trainer().await?;
+                let pokemon_trainer = application.ptcg(*region);
+                let region = (!all_regions).then_some(*region);
+                let all_cards = pokemon_trainer.export_pokemon_trainer(region).await?;
                 for card in all_cards {
                     let p: ExportCsv = card.into();
                     wtr.serialize(p).unwrap();
                 }
                 wtr.flush().unwrap();
             }
-            PtcgCommands::Strategy => {
+            PtcgCommands::Legality {
+                on,
+                all,
+                all_regions,
+            } => {
+                let on = on.unwrap_or_else(|| Utc::now().date_naive());
+                let pokemon_trainer = application.ptcg(*region);
+                let region = (!all_regions).then_some(*region);
+                pokemon_trainer
+                    .legality_report(on, region, *all, std::io::stdout())
+                    .await?;
+            }
+            PtcgCommands::Rotation {
+                format,
+                effective_date,
+                oldest_mark,
+            } => {
+                let rotation = Rotation {
+                    format: *format,
+                    effective_date: *effective_date,
+                    oldest_mark: oldest_mark.to_uppercase(),
+                };
+                application.ptcg(*region).set_rotation(&rotation).await?;
+            }
+            PtcgCommands::Strategy {
+                dry_run,
+                command: Some(command),
+            } => {
+                if *dry_run {
+                    return Err(eyre!("--dry-run only checks a strategy CSV from stdin"));
+                }
+                let ptcg = application.ptcg(*region);
+                match command {
+                    StrategyCommands::Import { author } => {
+                        let mut rdr = csv::Reader::from_reader(std::io::stdin());
+                        let rows = rdr.deserialize().collect::<Result<Vec<_>, _>>()?;
+                        let author = author.clone().or_else(|| std::env::var("USER").ok());
+                        ptcg.import_strategies(rows, author.as_deref(), std::io::stdout())
+                            .await?;
+                    }
+                    StrategyCommands::Export { exp } => {
+                        ptcg.export_strategies(exp.as_deref(), std::io::stdout())
+                            .await?;
+                    }
+                    StrategyCommands::Diff { exp, from, to } => {
+                        ptcg.diff_strategy(exp, *from, *to, std::io::stdout())
+                            .await?;
+                    }
+                    StrategyCommands::Apply { exps, version } => {
+                        ptcg.apply_strategies(exps, *version).await?;
+                    }
+                    StrategyCommands::History { exp } => {
+                        ptcg.strategy_history(exp, std::io::stdout()).await?;
+                    }
+                }
+            }
+            PtcgCommands::Strategy {
+                dry_run,
+                command: None,
+            } => {
                 let stdin = std::io::stdin();
                 let mut rdr = csv::Reader::from_reader(stdin);
+                let ptcg = application.ptcg(*region);
+                if *dry_run {
+                    let rows = rdr.deserialize().collect::<Result<Vec<_>, _>>()?;
+                    let problems = ptcg.strategy_dry_run(rows, std::io::stdout()).await?;
+                    if problems > 0 {
+                        return Err(eyre!("{problems} problems in the strategy file"));
+                    }
+                    return Ok(());
+                }
                 for result in rdr.deserialize() {
                     let record: PtcgStrategyRow = result?;
-                    let ptcg = application.ptcg();
                     let sources: Vec<Source> = serde_json::from_str(&record.strategy)?;
-                    ptcg.strategy_analyze(sources, record).await?;
+                    ptcg.strategy_analyze(sources, record, None).await?;
                 }
             }
         },
-        Commands::Yugioh(YugiohCommands::BuildExpLink) => {
-            application.yugioh().build_yugioh_expansion_link().await;
-        }
-        Commands::Yugioh(YugiohCommands::BuildPriLink) => loop {
-            application
-                .yugioh()
-                .build_yugioh_printing_link()
-                .await
-                .expect("ran out of expansion link to build printing link");
-            println!("done wait for 1 secs ...");
-            sleep(Duration::from_secs(1));
-        },
-        Commands::Yugioh(YugiohCommands::BuildDetail) => loop {
-            let waiting_secs = 1;
-            application
-                .yugioh()
-                .build_yugioh_printing_detail()
-                .await
-                .expect("ran out of printing link");
-            println!("done wait for {waiting_secs} secs ...");
-            sleep(Duration::from_secs(waiting_secs));
-        },
-        Commands::Yugioh(YugiohCommands::ExportCsv) => {
-            let wtr = std::io::stdout();
-            application
-                .yugioh()
-                .export_yugioh_printing_detail(wtr)
-                .await;
-        }
-        Commands::Ws(WsCommands::Scrape) => {
-            let ws = application.ws();
-            ws.scrape().await?;
+        Commands::Yugioh { locale, command } => {
+            let yugioh = application.yugioh(*locale);
+            match command {
+                YugiohCommands::ForbiddenLimited => yugioh.update_forbidden_limited().await?,
+                YugiohCommands::DownloadImages { bucket, base_path } => {
+                    yugioh.download_images(bucket.as_deref(), base_path).await?;
+                }
+                YugiohCommands::ExportCsv { as_of, all_locales } => {
+                    let locale = (!all_locales).then_some(*locale);
+                    let wtr = std::io::stdout();
+                    yugioh
+                        .export_yugioh_printing_detail(*as_of, locale, wtr)
+                        .await;
+                }
+            }
         }
-        Commands::Ws(WsCommands::ExportCsv) => {
+        Commands::Ws(WsCommands::ExportCsv { as_of }) => {
             let wtr = std::io::stdout();
             let ws = application.ws();
-            ws.export_csv(wtr).await?;
+            ws.export_csv(*as_of, wtr).await?;
         }
         Commands::Ws(WsCommands::DownloadImages) => {
             let ws = application.ws();
             ws.download_images("asia-tcg-marketplace-dataset", "ws_images")
                 .await?;
         }
-        Commands::OnePiece(OnePieceCommands::Scrape) => {
-            application.one_piece().scrape_one_piece().await?;
-        }
         Commands::OnePiece(OnePieceCommands::DownloadImages) => {
             application.one_piece().download_images().await?;
         }
@@ -202,18 +537,10 @@ async fn main() -> Result<()> {
                 .export_one_piece_product_csv(wtr)
                 .await?;
         }
-        Commands::PtcgJp(PtcgJpCommands::Exp) => {
-            let ptcg_jp = application.ptcg_jp();
-            ptcg_jp.update_exp().await?;
-        }
         Commands::PtcgJp(PtcgJpCommands::Card) => {
             let ptcg_jp = application.ptcg_jp();
             ptcg_jp.update_cards().await?;
         }
-        Commands::PtcgJp(PtcgJpCommands::Tc) => {
-            let ptcg_jp = application.ptcg_jp();
-            ptcg_jp.save_html().await?;
-        }
         Commands::PtcgJp(PtcgJpCommands::Extra) => {
             let ptcg_jp = application.ptcg_jp();
             ptcg_jp.build_extra().await?;
@@ -230,7 +557,7 @@ async fn main() -> Result<()> {
             let state = MyState {
                 pool,
                 client,
-                ptcg: application.ptcg(),
+                ptcg: application.ptcg(PtcgRegion::default()),
             };
             let app = Router::new()
                 .route("/", get(root))
@@ -247,6 +574,68 @@ async fn main() -> Result<()> {
             info!("server listening on port {}", host);
             axum::serve(listener, app).await?;
         }
+        Commands::Errors(commands) => match commands {
+            ErrorsCommands::List { game, all } => {
+                application
+                    .scrape_errors()
+                    .export_csv(*game, *all, std::io::stdout())
+                    .await?;
+            }
+            ErrorsCommands::Requeue { game } => {
+                application.scrape_errors().requeue(*game).await?;
+            }
+        },
+        Commands::Daemon { schedule } => {
+            let file = std::fs::File::open(schedule)?;
+            let entries: Vec<ScheduleEntry> = serde_json::from_reader(file)?;
+            application.daemon(&entries).await;
+        }
+        Commands::Runs(RunsCommands::List { game, limit }) => {
+            application
+                .runs()
+                .export_csv(*game, *limit, std::io::stdout())
+                .await?;
+        }
+        Commands::Runs(RunsCommands::Show { id }) => {
+            application.runs().show(*id, std::io::stdout()).await?;
+        }
+        Commands::Runs(RunsCommands::Changelog { id, summary }) => {
+            let runs = application.runs();
+            if *summary {
+                runs.summarize_changelog(*id, std::io::stdout()).await?;
+            } else {
+                runs.export_changelog(*id, std::io::stdout()).await?;
+            }
+        }
+        Commands::History(HistoryCommands::Show { table, card_key }) => {
+            application
+                .history()
+                .export_versions(*table, card_key, std::io::stdout())
+                .await?;
+        }
+        Commands::History(HistoryCommands::AsOf { table, at }) => {
+            application
+                .history()
+                .export_as_of(*table, *at, std::io::stdout())
+                .await?;
+        }
+        Commands::Jobs(commands) => match commands {
+            JobsCommands::Seed { queue } => {
+                application.jobs().seed(*queue).await?;
+            }
+            JobsCommands::Work { queue, lease_secs } => {
+                application
+                    .jobs()
+                    .work(*queue, Duration::from_secs(*lease_secs))
+                    .await?;
+            }
+            JobsCommands::Stats => {
+                application.jobs().export_stats(std::io::stdout()).await?;
+            }
+            JobsCommands::RetryDead { queue } => {
+                application.jobs().retry_dead(*queue).await?;
+            }
+        },
     }
     Ok(())
 }
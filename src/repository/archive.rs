@@ -0,0 +1,59 @@
+use std::io::{Read, Write};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use sha2::{Digest, Sha256};
+use sqlx::{Pool, Postgres};
+
+use super::RepositoryError;
+
+#[derive(Clone)]
+pub struct ArchiveRepository {
+    pub(crate) pool: Pool<Postgres>,
+}
+
+impl ArchiveRepository {
+    pub async fn save_page(&self, url: &str, source: &str) -> Result<(), RepositoryError> {
+        let hash = format!("{:x}", Sha256::digest(source.as_bytes()));
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(source.as_bytes())?;
+        let body = encoder.finish()?;
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            "INSERT INTO page_blob(hash, body) VALUES($1, $2) ON CONFLICT(hash) DO NOTHING",
+            hash,
+            body
+        )
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query!(
+            "INSERT INTO page_archive(url, hash) VALUES($1, $2)",
+            url,
+            hash
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+    pub async fn latest_page(&self, url: &str) -> Result<Option<String>, RepositoryError> {
+        let record = sqlx::query!(
+            "
+            SELECT b.body
+            FROM page_archive a
+            JOIN page_blob b ON a.hash = b.hash
+            WHERE a.url = $1
+            ORDER BY a.fetched_at DESC
+            LIMIT 1
+            ",
+            url
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(record) = record else {
+            return Ok(None);
+        };
+        let mut source = String::new();
+        GzDecoder::new(record.body.as_slice()).read_to_string(&mut source)?;
+        Ok(Some(source))
+    }
+}
@@ -0,0 +1,81 @@
+use crate::scraper::{
+    card_source::CardSource,
+    one_piece::{OnePieceCard, OnePieceScraper},
+    ptcg::{PtcgExpansion, PtcgScraper, ThePTCGCard},
+    tcg_collector::{PtcgJpExpansion, TcgCollectorCardDetail, TcgCollectorScraper},
+    ws::{WsCard, WsScraper},
+    yugioh::{YugiohCardDetail, YugiohScraper},
+};
+
+use super::{Repository, RepositoryError};
+
+// Where `application::pipeline::Pipeline` persists what a `CardSource` yields
+pub(crate) trait CardStore<S: CardSource> {
+    async fn save_expansion(&self, _expansion: &S::Expansion) -> Result<(), RepositoryError> {
+        Ok(())
+    }
+    // image is where the card image was stored, None when images are not downloaded
+    async fn save_card(&self, card: S::Card, image: Option<String>) -> Result<(), RepositoryError>;
+}
+
+impl CardStore<PtcgScraper> for Repository {
+    async fn save_expansion(&self, expansion: &PtcgExpansion) -> Result<(), RepositoryError> {
+        self.pokemon().upsert_ptcg_expansion(expansion).await
+    }
+    async fn save_card(
+        &self,
+        card: ThePTCGCard,
+        _image: Option<String>,
+    ) -> Result<(), RepositoryError> {
+        self.pokemon().upsert_the_ptcg_card(&card).await?;
+        Ok(())
+    }
+}
+
+impl CardStore<TcgCollectorScraper> for Repository {
+    async fn save_expansion(&self, expansion: &PtcgJpExpansion) -> Result<(), RepositoryError> {
+        self.save_ptcg_jp_expansions(vec![expansion.clone()]).await
+    }
+    async fn save_card(
+        &self,
+        card: TcgCollectorCardDetail,
+        _image: Option<String>,
+    ) -> Result<(), RepositoryError> {
+        self.save_tcg_collector(vec![card]).await
+    }
+}
+
+impl CardStore<YugiohScraper> for Repository {
+    async fn save_card(
+        &self,
+        detail: YugiohCardDetail,
+        image: Option<String>,
+    ) -> Result<(), RepositoryError> {
+        if let Some(card) = &detail.card {
+            self.upsert_yugioh_card(card).await?;
+            if let Some(image) = image {
+                self.set_yugioh_card_image(&card.card_id, &image).await?;
+            }
+        }
+        for printing in detail.printings {
+            self.upsert_yugioh_printing_detail(printing).await?;
+        }
+        Ok(())
+    }
+}
+
+impl CardStore<WsScraper> for Repository {
+    async fn save_card(&self, card: WsCard, _image: Option<String>) -> Result<(), RepositoryError> {
+        self.save_ws_cards(vec![card]).await
+    }
+}
+
+impl CardStore<OnePieceScraper> for Repository {
+    async fn save_card(
+        &self,
+        card: OnePieceCard,
+        _image: Option<String>,
+    ) -> Result<(), RepositoryError> {
+        self.upsert_one_piece(card).await
+    }
+}
@@ -0,0 +1,222 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::domain::Game;
+
+use super::{run::CurrentRun, RepositoryError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+pub fn diff(
+    old: &[(&'static str, Option<String>)],
+    new: &[(&'static str, Option<String>)],
+) -> Vec<FieldChange> {
+    old.iter()
+        .zip(new)
+        .filter(|((_, o), (_, n))| o != n)
+        .map(|((field, o), (_, n))| FieldChange {
+            field,
+            old: o.clone(),
+            new: n.clone(),
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeRecord {
+    pub run_id: Option<i32>,
+    pub game: String,
+    pub card_key: String,
+    pub change: String,
+    pub field: Option<String>,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+}
+
+pub struct ChangelogRepository {
+    pub(crate) pool: Pool<Postgres>,
+    pub(crate) current_run: Arc<CurrentRun>,
+}
+
+impl ChangelogRepository {
+    pub async fn record_new(&self, game: Game, card_key: &str) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "INSERT INTO card_changes(run_id, game, card_key, change) VALUES($1, $2, $3, 'new')",
+            self.current_run.id(),
+            game.to_string(),
+            card_key
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    pub async fn record_changed(
+        &self,
+        game: Game,
+        card_key: &str,
+        changes: &[FieldChange],
+    ) -> Result<(), RepositoryError> {
+        for change in changes {
+            sqlx::query!(
+                "
+                INSERT INTO card_changes(run_id, game, card_key, change, field, old_value, new_value)
+                VALUES($1, $2, $3, 'changed', $4, $5, $6)
+                ",
+                self.current_run.id(),
+                game.to_string(),
+                card_key,
+                change.field,
+                change.old,
+                change.new
+            )
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+    // printings the previous successful run of the same command saw in an expansion this run
+    // scraped again, but this run did not; ws and one_piece cards are grouped by their set
+    pub async fn record_removed(&self, run_id: i32) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+            WITH prev AS (
+                SELECT p.id FROM scrape_runs p JOIN scrape_runs c ON p.command = c.command
+                WHERE c.id = $1 AND p.id < c.id AND p.status = 'succeeded'
+                ORDER BY p.id DESC
+                LIMIT 1
+            )
+            INSERT INTO card_changes(run_id, game, card_key, change)
+            SELECT $1, 'ptcg', code || '|' || region, 'removed'
+            FROM pokemon_trainer_printing
+            WHERE last_seen_run = (SELECT id FROM prev)
+                AND (expansion_code, region) IN (
+                    SELECT expansion_code, region FROM pokemon_trainer_printing WHERE last_seen_run = $1
+                )
+            ",
+            run_id
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query!(
+            "
+            WITH prev AS (
+                SELECT p.id FROM scrape_runs p JOIN scrape_runs c ON p.command = c.command
+                WHERE c.id = $1 AND p.id < c.id AND p.status = 'succeeded'
+                ORDER BY p.id DESC
+                LIMIT 1
+            )
+            INSERT INTO card_changes(run_id, game, card_key, change)
+            SELECT $1, 'yugioh', card_id || '|' || locale || '|' || expansion_name || '|' || rarity, 'removed'
+            FROM yugioh_printing_detail
+            WHERE last_seen_run = (SELECT id FROM prev)
+                AND (locale, expansion_name) IN (
+                    SELECT locale, expansion_name FROM yugioh_printing_detail WHERE last_seen_run = $1
+                )
+            ",
+            run_id
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query!(
+            "
+            WITH prev AS (
+                SELECT p.id FROM scrape_runs p JOIN scrape_runs c ON p.command = c.command
+                WHERE c.id = $1 AND p.id < c.id AND p.status = 'succeeded'
+                ORDER BY p.id DESC
+                LIMIT 1
+            )
+            INSERT INTO card_changes(run_id, game, card_key, change)
+            SELECT $1, 'ws', code, 'removed'
+            FROM ws_cards
+            WHERE last_seen_run = (SELECT id FROM prev)
+                AND set_code IN (SELECT set_code FROM ws_cards WHERE last_seen_run = $1)
+            ",
+            run_id
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query!(
+            "
+            WITH prev AS (
+                SELECT p.id FROM scrape_runs p JOIN scrape_runs c ON p.command = c.command
+                WHERE c.id = $1 AND p.id < c.id AND p.status = 'succeeded'
+                ORDER BY p.id DESC
+                LIMIT 1
+            )
+            INSERT INTO card_changes(run_id, game, card_key, change)
+            SELECT $1, 'one_piece', code, 'removed'
+            FROM one_piece
+            WHERE last_seen_run = (SELECT id FROM prev)
+                AND set_name IN (SELECT set_name FROM one_piece WHERE last_seen_run = $1)
+            ",
+            run_id
+        )
+        .execute(&self.pool)
+        .await?;
+        sqlx::query!(
+            "
+            WITH prev AS (
+                SELECT p.id FROM scrape_runs p JOIN scrape_runs c ON p.command = c.command
+                WHERE c.id = $1 AND p.id < c.id AND p.status = 'succeeded'
+                ORDER BY p.id DESC
+                LIMIT 1
+            )
+            INSERT INTO card_changes(run_id, game, card_key, change)
+            SELECT $1, 'ptcg_jp', url, 'removed'
+            FROM tcg_collector
+            WHERE last_seen_run = (SELECT id FROM prev)
+                AND exp_code IN (SELECT exp_code FROM tcg_collector WHERE last_seen_run = $1)
+            ",
+            run_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    pub async fn list(&self, run_id: i32) -> Result<Vec<ChangeRecord>, RepositoryError> {
+        let records = sqlx::query_as!(
+            ChangeRecord,
+            "
+            SELECT run_id, game, card_key, change, field, old_value, new_value
+            FROM card_changes
+            WHERE run_id = $1
+            ORDER BY id
+            ",
+            run_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let old = [
+            ("name", Some("Pikachu".to_string())),
+            ("rarity", Some("C".to_string())),
+            ("number", None),
+        ];
+        let new = [
+            ("name", Some("Pikachu".to_string())),
+            ("rarity", Some("U".to_string())),
+            ("number", Some("025".to_string())),
+        ];
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "rarity");
+        assert_eq!(changes[0].old.as_deref(), Some("C"));
+        assert_eq!(changes[1].new.as_deref(), Some("025"));
+    }
+}
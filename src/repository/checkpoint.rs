@@ -0,0 +1,76 @@
+use sqlx::{Pool, Postgres};
+
+use super::RepositoryError;
+
+// Resume point of a long-running scrape, keyed by job name
+pub struct Checkpoint {
+    pub(crate) pool: Pool<Postgres>,
+    pub(crate) job: String,
+}
+
+impl Checkpoint {
+    pub async fn cursor(&self) -> Result<Option<String>, RepositoryError> {
+        let record = sqlx::query!(
+            "SELECT cursor FROM scrape_checkpoints WHERE job = $1",
+            &self.job
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(record.map(|r| r.cursor))
+    }
+    pub async fn save(&self, cursor: &str) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+            INSERT INTO scrape_checkpoints(job, cursor) VALUES($1, $2)
+            ON CONFLICT(job) DO UPDATE SET cursor = $2, updated_at = NOW()
+            ",
+            &self.job,
+            cursor
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    pub async fn clear(&self) -> Result<(), RepositoryError> {
+        sqlx::query!("DELETE FROM scrape_checkpoints WHERE job = $1", &self.job)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+    // items after the saved cursor; everything when the cursor is not among them
+    pub async fn remaining<T, F>(&self, items: Vec<T>, key: F) -> Result<Vec<T>, RepositoryError>
+    where
+        F: Fn(&T) -> String,
+    {
+        let cursor = self.cursor().await?;
+        Ok(skip_through(items, cursor.as_deref(), key))
+    }
+}
+
+fn skip_through<T, F>(mut items: Vec<T>, cursor: Option<&str>, key: F) -> Vec<T>
+where
+    F: Fn(&T) -> String,
+{
+    let Some(cursor) = cursor else {
+        return items;
+    };
+    match items.iter().position(|item| key(item) == cursor) {
+        Some(n) => items.split_off(n + 1),
+        None => items,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skip_through() {
+        let items = vec!["a", "b", "c"];
+        let key = |s: &&str| s.to_string();
+        assert_eq!(skip_through(items.clone(), None, key), vec!["a", "b", "c"]);
+        assert_eq!(skip_through(items.clone(), Some("b"), key), vec!["c"]);
+        assert!(skip_through(items.clone(), Some("c"), key).is_empty());
+        assert_eq!(skip_through(items, Some("z"), key), vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,132 @@
+use serde::Serialize;
+use sqlx::{
+    types::chrono::{DateTime, Utc},
+    Pool, Postgres,
+};
+
+use crate::{
+    domain::{HistoryTable, YugiohLocale, YugiohRarity},
+    scraper::{ws::WsCard, yugioh::YugiohPrinting},
+};
+
+use super::{RepositoryError, WsCardDto};
+
+#[derive(Debug, Serialize)]
+pub struct CardVersion {
+    pub card_key: String,
+    pub data: serde_json::Value,
+    pub valid_from: DateTime<Utc>,
+    pub valid_to: Option<DateTime<Utc>>,
+    pub run_id: Option<i32>,
+}
+
+pub struct HistoryRepository {
+    pub(crate) pool: Pool<Postgres>,
+}
+
+impl HistoryRepository {
+    pub async fn versions(
+        &self,
+        table: HistoryTable,
+        card_key: &str,
+    ) -> Result<Vec<CardVersion>, RepositoryError> {
+        let versions = sqlx::query_as!(
+            CardVersion,
+            "
+            SELECT card_key, data, valid_from, valid_to, run_id
+            FROM card_history
+            WHERE table_name = $1 AND card_key = $2
+            ORDER BY valid_from
+            ",
+            table.to_string(),
+            card_key
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(versions)
+    }
+    pub async fn as_of(
+        &self,
+        table: HistoryTable,
+        at: DateTime<Utc>,
+    ) -> Result<Vec<CardVersion>, RepositoryError> {
+        let versions = sqlx::query_as!(
+            CardVersion,
+            "
+            SELECT card_key, data, valid_from, valid_to, run_id
+            FROM card_history
+            WHERE table_name = $1 AND valid_from <= $2 AND (valid_to IS NULL OR valid_to > $2)
+            ORDER BY card_key
+            ",
+            table.to_string(),
+            at
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(versions)
+    }
+    pub async fn ws_cards_as_of(&self, at: DateTime<Utc>) -> Result<Vec<WsCard>, RepositoryError> {
+        let cards = sqlx::query_as!(
+            WsCardDto,
+            r#"
+            SELECT c.code AS "code!", c.name AS "name!", c.set_code AS "set_code!",
+                c.img_src AS "img_src!", c.rarity, c.set_name AS "set_name!"
+            FROM card_history h, jsonb_populate_record(NULL::ws_cards, h.data) c
+            WHERE h.table_name = 'ws_cards' AND h.valid_from <= $1 AND (h.valid_to IS NULL OR h.valid_to > $1)
+            "#,
+            at
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(cards.into_iter().map(WsCard::from).collect())
+    }
+    pub async fn yugioh_printing_as_of(
+        &self,
+        at: DateTime<Utc>,
+        locale: Option<YugiohLocale>,
+    ) -> Result<Vec<YugiohPrinting>, RepositoryError> {
+        let printings = sqlx::query!(
+            r#"
+            SELECT p.card_id AS "card_id!", p.locale AS "locale!", p.name AS "name!",
+                COALESCE(NULLIF(p.name_jp, ''), c.name_jp, '') AS "name_jp!",
+                COALESCE(NULLIF(p.name_en, ''), c.name_en, '') AS "name_en!", c.image AS "image?",
+                l.status AS "legality?", p.rarity AS "rarity!", p.number, p.release_date AS "release_date!",
+                p.remark, p.expansion_name AS "expansion_name!", p.expansion_code
+            FROM card_history h
+            CROSS JOIN jsonb_populate_record(NULL::yugioh_printing_detail, h.data) p
+            LEFT JOIN yugioh_card c ON c.card_id = p.card_id
+            LEFT JOIN yugioh_forbidden_limited l ON l.card_id = p.card_id AND l.effective_date = (
+                SELECT MAX(effective_date) FROM yugioh_forbidden_limited WHERE effective_date <= CAST($1::TIMESTAMPTZ AS DATE)
+            )
+            WHERE h.table_name = 'yugioh_printing_detail'
+                AND h.valid_from <= $1 AND (h.valid_to IS NULL OR h.valid_to > $1)
+                AND ($2::TEXT IS NULL OR p.locale = $2)
+            "#,
+            at,
+            locale.map(|locale| locale.to_string())
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| YugiohPrinting {
+            card_id: record.card_id,
+            locale: record.locale,
+            name: record.name,
+            name_jp: record.name_jp,
+            name_en: record.name_en,
+            rarity: YugiohRarity::from_code(&record.rarity),
+            number: record.number,
+            release_date: record.release_date,
+            remark: record.remark.unwrap_or_default(),
+            expansion_name: record.expansion_name,
+            r#ref: record.expansion_code,
+            image: record.image,
+            legality: record
+                .legality
+                .and_then(|legality| legality.parse().ok())
+                .unwrap_or_default(),
+        })
+        .collect();
+        Ok(printings)
+    }
+}
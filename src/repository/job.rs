@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::domain::{JobPayload, JobQueue};
+
+use super::RepositoryError;
+
+const RETRY_BACKOFF_SECS: f64 = 30.0;
+
+#[derive(Debug)]
+pub struct Job {
+    pub id: i64,
+    pub payload: JobPayload,
+    // also the lease token: a stale worker cannot finish a job someone else re-claimed
+    pub attempts: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct JobStats {
+    pub queue: String,
+    pub status: String,
+    pub count: i64,
+}
+
+pub struct JobRepository {
+    pub(crate) pool: Pool<Postgres>,
+}
+
+impl JobRepository {
+    pub async fn enqueue(&self, payloads: &[JobPayload]) -> Result<u64, RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let mut inserted = 0;
+        for payload in payloads {
+            inserted += sqlx::query!(
+                "INSERT INTO scrape_jobs(queue, payload) VALUES($1, $2) ON CONFLICT(queue, payload) WHERE status <> 'done' DO NOTHING",
+                payload.queue().to_string(),
+                serde_json::to_value(payload)?
+            )
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        tx.commit().await?;
+        Ok(inserted)
+    }
+    pub async fn claim(
+        &self,
+        queue: JobQueue,
+        lease: Duration,
+    ) -> Result<Option<Job>, RepositoryError> {
+        // jobs whose worker died on their last attempt never come back
+        sqlx::query!(
+            "
+            UPDATE scrape_jobs
+            SET status = 'dead', last_error = COALESCE(last_error, 'lease expired'), updated_at = NOW()
+            WHERE queue = $1 AND status = 'running' AND locked_until < NOW() AND attempts >= max_attempts
+            ",
+            queue.to_string()
+        )
+        .execute(&self.pool)
+        .await?;
+        let record = sqlx::query!(
+            "
+            UPDATE scrape_jobs
+            SET status = 'running', attempts = attempts + 1,
+                locked_until = NOW() + make_interval(secs => $2), updated_at = NOW()
+            WHERE id = (
+                SELECT id FROM scrape_jobs
+                WHERE queue = $1
+                    AND status IN ('pending', 'running')
+                    AND (locked_until IS NULL OR locked_until < NOW())
+                ORDER BY id
+                LIMIT 1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, payload, attempts
+            ",
+            queue.to_string(),
+            lease.as_secs_f64()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(record) = record else {
+            return Ok(None);
+        };
+        Ok(Some(Job {
+            id: record.id,
+            payload: serde_json::from_value(record.payload)?,
+            attempts: record.attempts,
+        }))
+    }
+    // time until the earliest pending or leased job can be claimed, None when nothing is left
+    pub async fn next_claimable(
+        &self,
+        queue: JobQueue,
+    ) -> Result<Option<Duration>, RepositoryError> {
+        let record = sqlx::query!(
+            "
+            SELECT EXTRACT(EPOCH FROM MIN(COALESCE(locked_until, NOW())) - NOW())::FLOAT8 AS wait
+            FROM scrape_jobs
+            WHERE queue = $1 AND status IN ('pending', 'running')
+            ",
+            queue.to_string()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(record
+            .wait
+            .map(|wait| Duration::from_secs_f64(wait.max(0.0))))
+    }
+    pub async fn complete(&self, job: &Job) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+            UPDATE scrape_jobs
+            SET status = 'done', locked_until = NULL, last_error = NULL, updated_at = NOW()
+            WHERE id = $1 AND attempts = $2
+            ",
+            job.id,
+            job.attempts
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    pub async fn fail(&self, job: &Job, error: &str) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+            UPDATE scrape_jobs
+            SET status = CASE WHEN attempts >= max_attempts THEN 'dead' ELSE 'pending' END,
+                locked_until = NOW() + make_interval(secs => $3 * power(2, attempts - 1)),
+                last_error = $4,
+                updated_at = NOW()
+            WHERE id = $1 AND attempts = $2
+            ",
+            job.id,
+            job.attempts,
+            RETRY_BACKOFF_SECS,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    pub async fn retry_dead(&self, queue: JobQueue) -> Result<u64, RepositoryError> {
+        let result = sqlx::query!(
+            "
+            UPDATE scrape_jobs
+            SET status = 'pending', attempts = 0, locked_until = NULL, updated_at = NOW()
+            WHERE queue = $1 AND status = 'dead'
+            ",
+            queue.to_string()
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+    pub async fn stats(&self) -> Result<Vec<JobStats>, RepositoryError> {
+        let stats = sqlx::query_as!(
+            JobStats,
+            r#"
+            SELECT queue, status, COUNT(*) AS "count!"
+            FROM scrape_jobs
+            GROUP BY queue, status
+            ORDER BY queue, status
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(stats)
+    }
+}
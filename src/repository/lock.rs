@@ -0,0 +1,28 @@
+use sqlx::{Postgres, Transaction};
+
+use super::{Repository, RepositoryError};
+
+// Transaction-scoped advisory lock: released on release() or when dropped, even on panic
+pub struct AdvisoryLock {
+    tx: Transaction<'static, Postgres>,
+}
+
+impl AdvisoryLock {
+    pub async fn release(self) -> Result<(), RepositoryError> {
+        self.tx.commit().await?;
+        Ok(())
+    }
+}
+
+impl Repository {
+    pub async fn try_lock(&self, name: &str) -> Result<Option<AdvisoryLock>, RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let locked = sqlx::query_scalar!(
+            r#"SELECT pg_try_advisory_xact_lock(hashtext($1)) AS "locked!""#,
+            name
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        Ok(locked.then_some(AdvisoryLock { tx }))
+    }
+}
@@ -1,41 +1,128 @@
+pub mod archive;
+pub mod card_store;
+pub mod changelog;
+pub mod checkpoint;
+pub mod history;
+pub mod job;
+pub mod lock;
 pub mod pokemon;
+pub mod run;
+pub mod scrape_error;
+pub mod strategy;
 
-use crate::domain::{LastFetchedAt, PtcgRarity};
+use crate::domain::{Game, LastFetchedAt, PtcgRarity, YugiohLocale, YugiohRarity};
 use crate::scraper::one_piece::{OnePieceCard, OnePieceCardRarity, OnePieceCardType};
 use crate::scraper::pokemon_wiki::PokemonWikiCard;
 use crate::scraper::tcg_collector::{
     PtcgJpCard, PtcgJpExpansion, TcgCollectorCardDetail, TcgCollectorCardRarity,
 };
 use crate::scraper::ws::WsCard;
-use crate::scraper::yugioh::YugiohPrinting;
+use crate::scraper::yugioh::{ForbiddenLimitedList, YugiohCard, YugiohPrinting};
+use archive::ArchiveRepository;
+use changelog::{diff, ChangelogRepository};
+use checkpoint::Checkpoint;
 use futures::stream::BoxStream;
 use futures::{StreamExt, TryStreamExt};
+use history::HistoryRepository;
+use job::JobRepository;
 use pokemon::PokemonRepository;
+use run::{CurrentRun, RunRepository, Upsert};
+use scrape_error::ScrapeErrorRepository;
 use sqlx::postgres::PgPoolOptions;
-use sqlx::{Pool, Postgres, Transaction};
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+use std::sync::Arc;
+use strategy::StrategyRepository;
 
 #[derive(Clone)]
 pub struct Repository {
     pool: Pool<Postgres>,
+    pub(crate) current_run: Arc<CurrentRun>,
 }
 
 impl Repository {
     pub fn pokemon(&self) -> PokemonRepository {
         PokemonRepository {
             pool: self.pool.clone(),
+            current_run: self.current_run.clone(),
+        }
+    }
+    pub fn archive(&self) -> ArchiveRepository {
+        ArchiveRepository {
+            pool: self.pool.clone(),
+        }
+    }
+    pub fn changelog(&self) -> ChangelogRepository {
+        ChangelogRepository {
+            pool: self.pool.clone(),
+            current_run: self.current_run.clone(),
+        }
+    }
+    pub fn checkpoint(&self, job: &str) -> Checkpoint {
+        Checkpoint {
+            pool: self.pool.clone(),
+            job: job.to_string(),
+        }
+    }
+    pub fn history(&self) -> HistoryRepository {
+        HistoryRepository {
+            pool: self.pool.clone(),
+        }
+    }
+    pub fn jobs(&self) -> JobRepository {
+        JobRepository {
+            pool: self.pool.clone(),
+        }
+    }
+    pub fn runs(&self) -> RunRepository {
+        RunRepository {
+            pool: self.pool.clone(),
+        }
+    }
+    pub fn scrape_errors(&self) -> ScrapeErrorRepository {
+        ScrapeErrorRepository {
+            pool: self.pool.clone(),
+            current_run: self.current_run.clone(),
+        }
+    }
+    pub fn strategies(&self) -> StrategyRepository {
+        StrategyRepository {
+            pool: self.pool.clone(),
+        }
+    }
+    // same pool, but writes are counted towards a new run
+    pub fn for_new_run(&self) -> Self {
+        Self {
+            pool: self.pool.clone(),
+            current_run: Arc::default(),
         }
     }
     pub fn from_dsn(url: &str) -> Result<Self, RepositoryError> {
         let pool = PgPoolOptions::new().connect_lazy(url)?;
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            current_run: Arc::default(),
+        })
     }
 
     pub async fn upsert_pokewiki(
         &self,
         cards: Vec<PokemonWikiCard>,
+        strategy_id: Option<i32>,
     ) -> Result<(), RepositoryError> {
+        let total = cards.len() as i32;
+        // one statement cannot update the same row twice
+        let mut seen = HashSet::new();
         let unzipped = cards
             .into_iter()
+            .filter(|card| {
+                seen.insert((
+                    card.number.clone(),
+                    card.name.clone(),
+                    card.exp_code.clone(),
+                    card.rarity.to_string(),
+                ))
+            })
             .fold((vec![], vec![], vec![], vec![]), |mut acc, card| {
                 acc.0.push(card.number);
                 acc.1.push(card.name);
@@ -43,23 +130,32 @@ impl Repository {
                 acc.3.push(card.rarity);
                 acc
             });
-        sqlx::query!(
-            "
-    INSERT INTO pokewiki(number, name, exp_code, rarity)
-    SELECT * FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[], $4::ptcg_rarity_enum[])
+        // rows written by a strategy remember its version
+        let inserted = sqlx::query!(
+            r#"
+    INSERT INTO pokewiki(number, name, exp_code, rarity, strategy_id)
+    SELECT *, $5::INT FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[], $4::ptcg_rarity_enum[])
     ON CONFLICT (number, name, rarity, exp_code)
-    DO NOTHING
-    ",
+    DO UPDATE SET strategy_id = EXCLUDED.strategy_id
+    WHERE EXCLUDED.strategy_id IS NOT NULL
+    RETURNING (xmax = 0) AS "inserted!"
+    "#,
             &unzipped.0,
             &unzipped.1,
             &unzipped.2,
             &unzipped.3 as &Vec<PtcgRarity>,
+            strategy_id
         )
-        .execute(&self.pool)
-        .await?;
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .filter(|r| r.inserted)
+        .count() as i32;
+        self.current_run.add(Upsert::Inserted, inserted);
+        self.current_run.add(Upsert::Unchanged, total - inserted);
         Ok(())
     }
-    pub fn get_tc_details(&self) -> BoxStream<Result<TcgCollectorCardDetail, RepositoryError>> {
+    pub fn get_tc_details(&self) -> BoxStream<'_, Result<TcgCollectorCardDetail, RepositoryError>> {
         sqlx::query_as!(
             TcgCollectorCardDetail,
             r#"SELECT name, number, exp_code, html, url, rarity AS "rarity: _" from tcg_collector"#
@@ -68,12 +164,31 @@ impl Repository {
         .map_err(RepositoryError::from)
         .boxed()
     }
+    pub async fn get_tc_details_by_exps(
+        &self,
+        exp_codes: &[String],
+    ) -> Result<Vec<TcgCollectorCardDetail>, RepositoryError> {
+        let exp_codes: Vec<String> = exp_codes.iter().map(|c| c.to_lowercase()).collect();
+        let cards = sqlx::query_as!(
+            TcgCollectorCardDetail,
+            r#"SELECT name, number, exp_code, html, url, rarity AS "rarity: _"
+            FROM tcg_collector
+            WHERE exp_code = ANY($1)
+            ORDER BY array_position($1, exp_code), number"#,
+            &exp_codes
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(cards)
+    }
     pub async fn get_ptcg_jp_expansions_links(&self) -> Result<Vec<String>, RepositoryError> {
         let links = sqlx::query!(
             "
             SELECT exp_link
             FROM pokemon_trainer_expansion pt
-            LEFT JOIN ptcg_jp_expansions jp on pt.code = jp.code"
+            LEFT JOIN ptcg_jp_expansions jp on pt.code = jp.code
+            WHERE pt.region = 'tw'
+            ORDER BY pt.code"
         )
         .fetch_all(&self.pool)
         .await?;
@@ -84,14 +199,34 @@ impl Repository {
         details: Vec<TcgCollectorCardDetail>,
     ) -> Result<(), RepositoryError> {
         for detail in details {
-            sqlx::query!(
-                "INSERT INTO tcg_collector(name, number, exp_code, html, url) VALUES ($1, $2, $3, $4, $5)",
+            let record = sqlx::query!(
+                r#"
+                WITH old AS (
+                    SELECT name, number, exp_code, html FROM tcg_collector WHERE url = $5
+                ), upserted AS (
+                    INSERT INTO tcg_collector(name, number, exp_code, html, url, last_seen_run)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT(url)
+                    DO UPDATE
+                    SET name = $1, number = $2, exp_code = $3, html = $4, last_seen_run = $6
+                    RETURNING name, number, exp_code, html
+                )
+                SELECT NOT EXISTS (SELECT 1 FROM old) AS "inserted!",
+                    (SELECT (name, number, exp_code, html) FROM old)
+                        IS DISTINCT FROM (u.name, u.number, u.exp_code, u.html) AS "changed!"
+                FROM upserted u
+                "#,
                 detail.name,
                 detail.number,
                 detail.exp_code,
                 detail.html,
-                detail.url
-            ).execute(&self.pool).await?;
+                detail.url,
+                self.current_run.id()
+            )
+            .fetch_one(&self.pool)
+            .await?;
+            self.current_run
+                .record(Upsert::from_flags(record.inserted, record.changed));
         }
         Ok(())
     }
@@ -102,9 +237,10 @@ impl Repository {
     ) -> Result<(), RepositoryError> {
         for c in card_rarities {
             sqlx::query!(
-                "UPDATE tcg_collector SET rarity = $1 WHERE url = $2",
+                "UPDATE tcg_collector SET rarity = $1, last_seen_run = $3 WHERE url = $2",
                 c.rarity as PtcgRarity,
-                c.url
+                c.url,
+                self.current_run.id()
             )
             .execute(&self.pool)
             .await?;
@@ -137,7 +273,7 @@ impl Repository {
     pub async fn save_ptcg_jp_cards(&self, cards: Vec<PtcgJpCard>) -> Result<(), RepositoryError> {
         for card in cards {
             dbg!(&card);
-            sqlx::query!(
+            let updated = sqlx::query!(
                 "
                 UPDATE pokemon_trainer_printing SET
                 name_en = $1,
@@ -154,105 +290,251 @@ impl Repository {
                 card.desc,
             )
             .execute(&self.pool)
-            .await?;
+            .await?
+            .rows_affected() as i32;
+            self.current_run.add(Upsert::Updated, updated);
         }
         Ok(())
     }
-    pub async fn upsert_yugioh_expansion_link(&self, url: &str) {
-        sqlx::query!(
-            "INSERT INTO yugioh_expansion_link(url) VALUES($1) ON CONFLICT(url) DO NOTHING",
-            url
+    pub async fn upsert_yugioh_printing_detail(
+        &self,
+        detail: YugiohPrinting,
+    ) -> Result<(), RepositoryError> {
+        let rarity = detail.rarity.to_string();
+        let record = sqlx::query!(
+            r#"
+            WITH old AS (
+                SELECT name, name_jp, name_en, number, release_date, remark, expansion_code
+                FROM yugioh_printing_detail
+                WHERE card_id = $9 AND locale = $11 AND expansion_name = $7 AND rarity = $3
+            ), upserted AS (
+                INSERT INTO yugioh_printing_detail(
+                name_jp, name_en, rarity, number, release_date, remark, expansion_name, expansion_code, card_id,
+                last_seen_run, locale, name)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+                ON CONFLICT(card_id, locale, expansion_name, rarity)
+                DO UPDATE
+                SET name_jp = $1, name_en = $2, number = $4, release_date = $5, remark = $6, expansion_code = $8,
+                    last_seen_run = $10, name = $12
+                RETURNING 1
+            )
+            SELECT NOT EXISTS (SELECT 1 FROM old) AS "inserted!",
+                o.name AS "name?", o.name_jp AS "name_jp?", o.name_en AS "name_en?", o.number AS "number?",
+                o.release_date AS "release_date?", o.remark AS "remark?", o.expansion_code AS "expansion_code?"
+            FROM upserted LEFT JOIN old o ON TRUE
+            "#,
+            detail.name_jp,
+            detail.name_en,
+            rarity,
+            detail.number,
+            detail.release_date,
+            detail.remark,
+            detail.expansion_name,
+            detail.r#ref,
+            detail.card_id,
+            self.current_run.id(),
+            detail.locale,
+            detail.name
         )
-        .execute(&self.pool)
-        .await
-        .unwrap();
+        .fetch_one(&self.pool)
+        .await?;
+        let card_key = format!(
+            "{}|{}|{}|{}",
+            detail.card_id, detail.locale, detail.expansion_name, rarity
+        );
+        if record.inserted {
+            self.current_run.record(Upsert::Inserted);
+            return self.changelog().record_new(Game::Yugioh, &card_key).await;
+        }
+        let changes = diff(
+            &[
+                ("name", record.name),
+                ("name_jp", record.name_jp),
+                ("name_en", record.name_en),
+                ("number", record.number),
+                ("release_date", record.release_date),
+                ("remark", record.remark),
+                ("expansion_code", record.expansion_code),
+            ],
+            &[
+                ("name", Some(detail.name)),
+                ("name_jp", Some(detail.name_jp)),
+                ("name_en", Some(detail.name_en)),
+                ("number", detail.number),
+                ("release_date", Some(detail.release_date)),
+                ("remark", Some(detail.remark)),
+                ("expansion_code", detail.r#ref),
+            ],
+        );
+        if changes.is_empty() {
+            self.current_run.record(Upsert::Unchanged);
+        } else {
+            self.current_run.record(Upsert::Updated);
+            self.changelog()
+                .record_changed(Game::Yugioh, &card_key, &changes)
+                .await?;
+        }
+        Ok(())
     }
-    pub async fn get_yugioh_expansion_link(&self) -> Option<ExpansionLink> {
-        let mut conn = self.pool.begin().await.unwrap();
-        let link = sqlx::query!(
-            "
-            SELECT url FROM yugioh_expansion_link
-            LIMIT 1 FOR UPDATE SKIP LOCKED
+    pub async fn upsert_yugioh_card(&self, card: &YugiohCard) -> Result<(), RepositoryError> {
+        sqlx::query!(
             "
+            INSERT INTO yugioh_card(
+            card_id, name_jp, name_en, attribute, level, rank, link_rating, types, atk, def,
+            pendulum_scale, pendulum_effect, link_arrows, effect_text, img_src)
+            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            ON CONFLICT(card_id)
+            DO UPDATE
+            SET name_jp = $2, name_en = $3, attribute = $4, level = $5, rank = $6, link_rating = $7,
+                types = $8, atk = $9, def = $10, pendulum_scale = $11, pendulum_effect = $12,
+                link_arrows = $13, effect_text = $14, img_src = COALESCE($15, yugioh_card.img_src),
+                updated_at = NOW()
+            ",
+            card.card_id,
+            card.name_jp,
+            card.name_en,
+            card.attribute,
+            card.level,
+            card.rank,
+            card.link_rating,
+            &card.types,
+            card.atk,
+            card.def,
+            card.pendulum_scale,
+            card.pendulum_effect,
+            &card.link_arrows,
+            card.effect_text,
+            card.img_src
         )
-        .fetch_optional(&mut *conn)
-        .await
-        .unwrap();
-        link.map(|link| ExpansionLink {
-            conn,
-            url: link.url,
-        })
-    }
-    pub async fn get_yugioh_printing_link(&self) -> Option<PrintingLink> {
-        let mut conn = self.pool.begin().await.unwrap();
-        let link =
-            sqlx::query!("SELECT url FROM yugioh_printing_link LIMIT 1 FOR UPDATE SKIP LOCKED")
-                .fetch_optional(&mut *conn)
-                .await
-                .unwrap();
-        link.map(|link| PrintingLink {
-            conn,
-            url: link.url,
-        })
+        .execute(&self.pool)
+        .await?;
+        Ok(())
     }
-    pub async fn upsert_yugioh_printing_link(&self, url: &str) {
+    // replaces the list published for that date
+    pub async fn save_yugioh_forbidden_limited(
+        &self,
+        list: &ForbiddenLimitedList,
+    ) -> Result<(), RepositoryError> {
+        let (card_ids, statuses): (Vec<String>, Vec<String>) = list
+            .cards
+            .iter()
+            .map(|(card_id, status)| (card_id.clone(), status.to_string()))
+            .unzip();
+        let mut tx = self.pool.begin().await?;
         sqlx::query!(
-            "INSERT INTO yugioh_printing_link(url) VALUES($1) ON CONFLICT(url) DO NOTHING",
-            url
+            "DELETE FROM yugioh_forbidden_limited WHERE effective_date = $1",
+            list.effective_date
         )
-        .execute(&self.pool)
-        .await
-        .unwrap();
-    }
-    pub async fn upsert_yugioh_printing_detail(&self, detail: YugiohPrinting) {
+        .execute(&mut *tx)
+        .await?;
         sqlx::query!(
             "
-            INSERT INTO yugioh_printing_detail(
-            name_jp, name_en, rarity, number, release_date, remark, expansion_name, expansion_code, card_id)
-            VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9)
-            ON CONFLICT(card_id, expansion_name, rarity)
-            DO UPDATE
-            SET name_jp = $1, name_en = $2, number = $4, release_date = $5, remark = $6, expansion_code = $7
+            INSERT INTO yugioh_forbidden_limited(effective_date, card_id, status)
+            SELECT $1, * FROM UNNEST($2::TEXT[], $3::TEXT[])
             ",
-            detail.name_jp,
-            detail.name_en,
-            detail.rarity,
-            detail.number,
-            detail.release_date,
-            detail.remark,
-            detail.expansion_name,
-            detail.r#ref,
-            detail.card_id
+            list.effective_date,
+            &card_ids,
+            &statuses
         )
-        .execute(&self.pool)
-        .await
-        .unwrap();
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
     }
-    pub async fn get_yugioh_printing(&self) -> Result<Vec<YugiohPrinting>, RepositoryError> {
-        let printings = sqlx::query!("SELECT * FROM yugioh_printing_detail")
-            .fetch_all(&self.pool)
-            .await?
+    pub async fn get_yugioh_images_to_download(
+        &self,
+    ) -> Result<Vec<(String, String)>, RepositoryError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT card_id, img_src AS "img_src!" FROM yugioh_card
+            WHERE img_src IS NOT NULL AND image IS NULL
+            ORDER BY card_id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(records
             .into_iter()
-            .map(|record| YugiohPrinting {
-                card_id: record.card_id,
-                name_jp: record.name_jp,
-                name_en: record.name_en,
-                rarity: record.rarity,
-                number: record.number,
-                release_date: record.release_date,
-                remark: record.remark.unwrap_or(String::from("")),
-                expansion_name: record.expansion_name,
-                r#ref: record.expansion_code,
-            })
-            .collect();
+            .map(|record| (record.card_id, record.img_src))
+            .collect())
+    }
+    pub async fn set_yugioh_card_image(
+        &self,
+        card_id: &str,
+        image: &str,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "UPDATE yugioh_card SET image = $2 WHERE card_id = $1",
+            card_id,
+            image
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    // printings of other locales take their Japanese and English names from the ja card
+    pub async fn get_yugioh_printing(
+        &self,
+        locale: Option<YugiohLocale>,
+    ) -> Result<Vec<YugiohPrinting>, RepositoryError> {
+        let printings = sqlx::query!(
+            r#"
+            SELECT p.card_id, p.locale, p.name, p.rarity, p.number, p.release_date, p.remark,
+                p.expansion_name, p.expansion_code,
+                COALESCE(NULLIF(p.name_jp, ''), c.name_jp, '') AS "name_jp!",
+                COALESCE(NULLIF(p.name_en, ''), c.name_en, '') AS "name_en!",
+                c.image AS "image?", l.status AS "legality?"
+            FROM yugioh_printing_detail p
+            LEFT JOIN yugioh_card c ON c.card_id = p.card_id
+            LEFT JOIN yugioh_current_legality l ON l.card_id = p.card_id
+            WHERE $1::TEXT IS NULL OR p.locale = $1
+            "#,
+            locale.map(|locale| locale.to_string())
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|record| YugiohPrinting {
+            card_id: record.card_id,
+            locale: record.locale,
+            name: record.name,
+            name_jp: record.name_jp,
+            name_en: record.name_en,
+            rarity: YugiohRarity::from_code(&record.rarity),
+            number: record.number,
+            release_date: record.release_date,
+            remark: record.remark.unwrap_or(String::from("")),
+            expansion_name: record.expansion_name,
+            r#ref: record.expansion_code,
+            image: record.image,
+            legality: record
+                .legality
+                .and_then(|legality| legality.parse().ok())
+                .unwrap_or_default(),
+        })
+        .collect();
         Ok(printings)
     }
 
-    pub async fn upsert_one_piece(&self, card: OnePieceCard) {
-        sqlx::query!(
-            "
-            INSERT INTO one_piece(code, name, img_src, rarity, set_name, type, get_info)
-            VALUES($1, $2, $3, $4, $5, $6, $7)",
+    pub async fn upsert_one_piece(&self, card: OnePieceCard) -> Result<(), RepositoryError> {
+        let record = sqlx::query!(
+            r#"
+            WITH old AS (
+                SELECT name, img_src, rarity, set_name, type, get_info FROM one_piece WHERE code = $1
+            ), upserted AS (
+                INSERT INTO one_piece(code, name, img_src, rarity, set_name, type, get_info, last_seen_run)
+                VALUES($1, $2, $3, $4, $5, $6, $7, $8)
+                ON CONFLICT(code)
+                DO UPDATE
+                SET name = $2, img_src = $3, rarity = $4, set_name = $5, type = $6, get_info = $7,
+                    last_seen_run = $8
+                RETURNING name, img_src, rarity, set_name, type, get_info
+            )
+            SELECT NOT EXISTS (SELECT 1 FROM old) AS "inserted!",
+                (SELECT (name, img_src, rarity, set_name, type, get_info) FROM old)
+                    IS DISTINCT FROM (u.name, u.img_src, u.rarity, u.set_name, u.type, u.get_info) AS "changed!"
+            FROM upserted u
+            "#,
             card.code,
             card.name,
             card.img_src,
@@ -260,12 +542,15 @@ impl Repository {
             card.set_name,
             card.r#type as OnePieceCardType,
             card.get_info,
+            self.current_run.id()
         )
-        .execute(&self.pool)
-        .await
-        .unwrap();
+        .fetch_one(&self.pool)
+        .await?;
+        self.current_run
+            .record(Upsert::from_flags(record.inserted, record.changed));
+        Ok(())
     }
-    pub fn list_one_piece(&self) -> BoxStream<Result<OnePieceCard, RepositoryError>> {
+    pub fn list_one_piece(&self) -> BoxStream<'_, Result<OnePieceCard, RepositoryError>> {
         sqlx::query_as!(
             OnePieceCardDto,
             r#"
@@ -278,51 +563,67 @@ impl Repository {
         .map_err(|e|e.into())
         .boxed()
     }
-    pub async fn get_ws_progress(&self) -> Result<i32, RepositoryError> {
-        let record = sqlx::query!(
-            "SELECT current_page FROM ws_progress WHERE id = (SELECT id FROM ws_progress_id_seq)"
-        )
-        .fetch_one(&self.pool)
-        .await?;
-        Ok(record.current_page)
-    }
-    pub async fn update_ws_progress(&self, current_page: i32) -> Result<(), RepositoryError> {
-        sqlx::query!("UPDATE ws_progress SET current_page = $1 WHERE id = (SELECT id FROM ws_progress_id_seq)", current_page)
-            .execute(&self.pool)
-            .await?;
-        Ok(())
-    }
     pub async fn save_ws_cards(&self, cards: Vec<WsCard>) -> Result<(), RepositoryError> {
-        let unzipped = cards.into_iter().fold(
-            (vec![], vec![], vec![], vec![], vec![], vec![]),
-            |mut acc, card| {
-                acc.0.push(card.code);
-                acc.1.push(card.name);
-                acc.2.push(card.set_code);
-                acc.3.push(card.img_src);
-                acc.4.push(card.rarity.unwrap_or("UNKNOWN".to_string()));
-                acc.5.push(card.set_name);
-                acc
-            },
-        );
-        sqlx::query!(
-            "
-            INSERT INTO ws_cards(code, name, set_code, img_src, rarity, set_name)
-            SELECT *
-            FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[], $4::TEXT[], $5::TEXT[], $6::TEXT[])
-            ",
+        let total = cards.len() as i32;
+        // one statement cannot update the same row twice, a page can list a code again
+        let mut seen = HashSet::new();
+        let unzipped = cards
+            .into_iter()
+            .filter(|card| seen.insert(card.code.clone()))
+            .fold(
+                (vec![], vec![], vec![], vec![], vec![], vec![]),
+                |mut acc, card| {
+                    acc.0.push(card.code);
+                    acc.1.push(card.name);
+                    acc.2.push(card.set_code);
+                    acc.3.push(card.img_src);
+                    acc.4.push(card.rarity.unwrap_or("UNKNOWN".to_string()));
+                    acc.5.push(card.set_name);
+                    acc
+                },
+            );
+        // every row seen gets last_seen_run, the old values only tell updated and unchanged apart
+        let records = sqlx::query!(
+            r#"
+            WITH old AS (
+                SELECT code, name, set_code, img_src, rarity, set_name FROM ws_cards
+                WHERE code = ANY($1)
+            ), upserted AS (
+                INSERT INTO ws_cards(code, name, set_code, img_src, rarity, set_name, last_seen_run)
+                SELECT *, $7::INTEGER
+                FROM UNNEST($1::TEXT[], $2::TEXT[], $3::TEXT[], $4::TEXT[], $5::TEXT[], $6::TEXT[])
+                ON CONFLICT(code)
+                DO UPDATE
+                SET name = EXCLUDED.name, set_code = EXCLUDED.set_code, img_src = EXCLUDED.img_src,
+                    rarity = EXCLUDED.rarity, set_name = EXCLUDED.set_name,
+                    last_seen_run = EXCLUDED.last_seen_run
+                RETURNING code, name, set_code, img_src, rarity, set_name
+            )
+            SELECT o.code IS NULL AS "inserted!",
+                (o.name, o.set_code, o.img_src, o.rarity, o.set_name)
+                    IS DISTINCT FROM (u.name, u.set_code, u.img_src, u.rarity, u.set_name) AS "changed!"
+            FROM upserted u LEFT JOIN old o ON o.code = u.code
+            "#,
             &unzipped.0,
             &unzipped.1,
             &unzipped.2,
             &unzipped.3,
             &unzipped.4,
-            &unzipped.5
+            &unzipped.5,
+            self.current_run.id() as Option<i32>
         )
-        .execute(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
+        for record in records {
+            self.current_run
+                .record(Upsert::from_flags(record.inserted, record.changed));
+        }
+        // duplicates of a code in the batch count as unchanged
+        self.current_run
+            .add(Upsert::Unchanged, total - unzipped.0.len() as i32);
         Ok(())
     }
-    pub fn get_ws_cards(&self) -> BoxStream<Result<WsCard, RepositoryError>> {
+    pub fn get_ws_cards(&self) -> BoxStream<'_, Result<WsCard, RepositoryError>> {
         sqlx::query_as!(
             WsCardDto,
             "SELECT code, name, set_code, img_src, rarity, set_name FROM ws_cards"
@@ -388,36 +689,8 @@ impl From<OnePieceCardDto> for OnePieceCard {
 pub enum RepositoryError {
     #[error("backend error")]
     BackendError(#[from] sqlx::Error),
-}
-
-pub struct ExpansionLink<'a> {
-    conn: Transaction<'a, Postgres>,
-    pub url: String,
-}
-
-impl<'a> ExpansionLink<'a> {
-    pub async fn done(mut self) {
-        sqlx::query!(
-            "DELETE FROM yugioh_expansion_link WHERE url = $1",
-            &self.url
-        )
-        .execute(&mut *self.conn)
-        .await
-        .unwrap();
-        self.conn.commit().await.unwrap();
-    }
-}
-pub struct PrintingLink<'a> {
-    conn: Transaction<'a, Postgres>,
-    pub url: String,
-}
-
-impl<'a> PrintingLink<'a> {
-    pub async fn done(mut self) {
-        sqlx::query!("DELETE FROM yugioh_printing_link WHERE url = $1", &self.url)
-            .execute(&mut *self.conn)
-            .await
-            .unwrap();
-        self.conn.commit().await.unwrap();
-    }
+    #[error("page compression error {0}")]
+    Compression(#[from] std::io::Error),
+    #[error("job payload error {0}")]
+    Payload(#[from] serde_json::Error),
 }
@@ -1,39 +1,50 @@
-use crate::domain::{LastFetchedAt, PokemonCard, PtcgRarity};
-use crate::scraper::ptcg::{PtcgExpansion, ThePTCGCard};
+use crate::domain::{
+    Game, LastFetchedAt, PokemonCard, PtcgPrintingAttributes, PtcgRarity, PtcgRegion,
+};
+use crate::rotation::Rotation;
+use crate::scraper::ptcg::{PtcgExpansion, PtcgSkill, ThePTCGCard};
 use crate::scraper::tcg_collector::{PtcgJpCard, TcgCollectorCardDetail};
 use futures::stream::BoxStream;
 use futures::{StreamExt, TryStreamExt};
 use sqlx::{Pool, Postgres};
+use std::sync::Arc;
 
+use super::changelog::{diff, ChangelogRepository, FieldChange};
+use super::run::{CurrentRun, Upsert};
 use super::RepositoryError;
 
 pub struct PokemonRepository {
     pub(crate) pool: Pool<Postgres>,
+    pub(crate) current_run: Arc<CurrentRun>,
 }
 impl PokemonRepository {
-    pub(crate) fn get_ptcg_exp_codes(&self) -> BoxStream<Result<String, RepositoryError>> {
-        sqlx::query!("SELECT code FROM pokemon_trainer_expansion")
-            .fetch(&self.pool)
-            .map_ok(|c| c.code)
-            .map_err(|e| e.into())
-            .boxed()
+    fn changelog(&self) -> ChangelogRepository {
+        ChangelogRepository {
+            pool: self.pool.clone(),
+            current_run: self.current_run.clone(),
+        }
     }
-
-    pub(crate) fn get_ptcg_card_codes(&self) -> BoxStream<Result<String, RepositoryError>> {
-        sqlx::query!("SELECT code FROM pokemon_trainer_printing")
-            .fetch(&self.pool)
-            .map_ok(|c| c.code)
-            .map_err(|e| e.into())
-            .boxed()
+    pub(crate) fn get_ptcg_exp_codes(
+        &self,
+        region: PtcgRegion,
+    ) -> BoxStream<'_, Result<String, RepositoryError>> {
+        sqlx::query!(
+            "SELECT code FROM pokemon_trainer_expansion WHERE region = $1",
+            region.to_string()
+        )
+        .fetch(&self.pool)
+        .map_ok(|c| c.code)
+        .map_err(|e| e.into())
+        .boxed()
     }
 
     pub async fn upsert_ptcg_expansion(&self, exp: &PtcgExpansion) -> Result<(), RepositoryError> {
         sqlx::query!(
-              "INSERT INTO pokemon_trainer_expansion(id, code, series, name, release_date, updated_at)
-              VALUES(gen_random_uuid(), $1, $2, $3, $4, NOW())
-              ON CONFLICT(code)
-              DO UPDATE SET code = $1, series = $2, name = $3, release_date = $4, updated_at = NOW()",
-              exp.code, exp.series, exp.name, exp.release_date)
+              "INSERT INTO pokemon_trainer_expansion(id, code, series, name, release_date, updated_at, region)
+              VALUES(gen_random_uuid(), $1, $2, $3, $4, NOW(), $5)
+              ON CONFLICT(code, region)
+              DO UPDATE SET series = $2, name = $3, release_date = $4, updated_at = NOW()",
+              exp.code, exp.series, exp.name, exp.release_date, exp.region.to_string())
         .execute(&self.pool)
         .await?;
         Ok(())
@@ -42,47 +53,100 @@ impl PokemonRepository {
         &self,
         fetchable_codes: Vec<String>,
         set_code: &str,
+        region: PtcgRegion,
     ) -> Result<(), RepositoryError> {
         for code in fetchable_codes {
             sqlx::query!(
-                "INSERT INTO pokemon_trainer_fetchable_card(code, fetched, expansion_code) VALUES($1, False, $2)
-                ON CONFLICT(code)
+                "INSERT INTO pokemon_trainer_fetchable_card(code, fetched, expansion_code, region) VALUES($1, False, $2, $3)
+                ON CONFLICT(code, region)
                 DO UPDATE
-                    SET code = $1, fetched = False, expansion_code = $2",
+                    SET fetched = False, expansion_code = $2",
                 code,
-                set_code
+                set_code,
+                region.to_string()
             )
             .execute(&self.pool)
             .await?;
         }
         Ok(())
     }
-    pub fn get_fetchable(&self) -> BoxStream<Result<(String, String), RepositoryError>> {
+    // every region when None
+    pub fn get_fetchable(
+        &self,
+        region: Option<PtcgRegion>,
+    ) -> BoxStream<'_, Result<(String, String, PtcgRegion), RepositoryError>> {
         sqlx::query!(
-            "SELECT fetchable.code, fetchable.expansion_code
+            "SELECT fetchable.code, fetchable.expansion_code, fetchable.region
             FROM pokemon_trainer_fetchable_card fetchable
-            WHERE fetched = false
-            "
+            WHERE fetched = false AND ($1::TEXT IS NULL OR region = $1)
+            ",
+            region.map(|r| r.to_string())
         )
         .fetch(&self.pool)
-        .map_ok(|s| (s.code, s.expansion_code))
+        .map_ok(|s| {
+            (
+                s.code,
+                s.expansion_code,
+                s.region.parse().unwrap_or_default(),
+            )
+        })
         .map_err(RepositoryError::from)
         .boxed()
     }
-    pub fn get_fetchable_by_code(&self, code: &str) -> BoxStream<Result<String, RepositoryError>> {
+    pub fn get_fetchable_by_code(
+        &self,
+        code: &str,
+        region: PtcgRegion,
+    ) -> BoxStream<'_, Result<String, RepositoryError>> {
         sqlx::query!(
-            "SELECT code FROM pokemon_trainer_fetchable_card WHERE expansion_code = $1",
-            code
+            "SELECT code FROM pokemon_trainer_fetchable_card WHERE expansion_code = $1 AND region = $2",
+            code,
+            region.to_string()
         )
         .fetch(&self.pool)
         .map_ok(|s| s.code)
         .map_err(RepositoryError::from)
         .boxed()
     }
-    pub async fn fetched(&self, code: &str) {
+    // (number, name_en) of every printing in the expansion
+    pub async fn get_ptcg_printing_names(
+        &self,
+        set_code: &str,
+        region: PtcgRegion,
+    ) -> Result<Vec<(String, Option<String>)>, RepositoryError> {
+        let printings = sqlx::query!(
+            "SELECT number, name_en FROM pokemon_trainer_printing WHERE expansion_code = $1 AND region = $2 ORDER BY number",
+            set_code,
+            region.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(printings
+            .into_iter()
+            .map(|p| (p.number, p.name_en))
+            .collect())
+    }
+    pub async fn set_printing_strategy(
+        &self,
+        code: &str,
+        region: PtcgRegion,
+        strategy_id: i32,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "UPDATE pokemon_trainer_printing SET strategy_id = $3 WHERE code = $1 AND region = $2",
+            code,
+            region.to_string(),
+            strategy_id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    pub async fn fetched(&self, code: &str, region: PtcgRegion) {
         sqlx::query!(
-            "UPDATE pokemon_trainer_fetchable_card SET fetched = True WHERE code = $1",
-            code
+            "UPDATE pokemon_trainer_fetchable_card SET fetched = True WHERE code = $1 AND region = $2",
+            code,
+            region.to_string()
         )
         .execute(&self.pool)
         .await
@@ -92,15 +156,41 @@ impl PokemonRepository {
         &self,
         ids: Vec<String>,
         rarity: &PtcgRarity,
+        region: PtcgRegion,
     ) -> Result<(), RepositoryError> {
-        dbg!(rarity.to_string());
+        let changed = sqlx::query!(
+            "
+            SELECT code, rarity FROM pokemon_trainer_printing
+            WHERE code = ANY($2) AND region = $3 AND rarity IS DISTINCT FROM $1
+            ",
+            rarity.to_string(),
+            &ids,
+            region.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
         sqlx::query!(
-            "UPDATE pokemon_trainer_printing SET rarity = $1 WHERE code = ANY($2)",
+            "
+            UPDATE pokemon_trainer_printing SET rarity = $1, last_seen_run = $3
+            WHERE code = ANY($2) AND region = $4
+            ",
             rarity.to_string(),
-            &ids
+            &ids,
+            self.current_run.id(),
+            region.to_string()
         )
         .execute(&self.pool)
         .await?;
+        for record in changed {
+            let change = FieldChange {
+                field: "rarity",
+                old: record.rarity,
+                new: Some(rarity.to_string()),
+            };
+            self.changelog()
+                .record_changed(Game::Ptcg, &card_key(&record.code, region), &[change])
+                .await?;
+        }
         Ok(())
     }
     pub async fn save_extra(&self, card: PtcgJpCard) -> Result<(), RepositoryError> {
@@ -124,49 +214,275 @@ impl PokemonRepository {
         dbg!(results);
         Ok(())
     }
-    pub async fn upsert_the_ptcg_card(&self, card: &ThePTCGCard) {
-        sqlx::query!(
+    // returns the code the printing is stored under, an existing row keeps its own
+    pub async fn upsert_the_ptcg_card(
+        &self,
+        card: &ThePTCGCard,
+    ) -> Result<String, RepositoryError> {
+        // the row the upsert conflicts with, locked so no other run rewrites it in between
+        let mut tx = self.pool.begin().await?;
+        let old = sqlx::query!(
+            "
+            SELECT name, kind, number, expansion_code, evolve_marker, img_src, hp, weak_point,
+                resist, escape, expansion_symbol, energy, artist, regulation_mark
+            FROM pokemon_trainer_printing
+            WHERE name = $1 AND number = $2 AND expansion_code = $3 AND region = $4
+            FOR UPDATE
+            ",
+            card.name,
+            card.number,
+            card.set_code,
+            card.region.to_string()
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let code = sqlx::query!(
             "
-                   INSERT INTO pokemon_trainer_printing(code, kind, name, number, expansion_code)
-                   VALUES($1, $2, $3, $4, $5)
-                   ON CONFLICT(name, number, expansion_code)
+                   INSERT INTO pokemon_trainer_printing(
+                   code, kind, name, number, expansion_code, last_seen_run, evolve_marker, img_src, hp,
+                   weak_point, resist, escape, expansion_symbol, energy, artist, region, regulation_mark)
+                   VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, NULLIF($15, ''), $16, $17)
+                   ON CONFLICT(name, number, expansion_code, region)
                    DO UPDATE
-                   SET kind = $2, name = $3, number = $4, expansion_code = $5
+                   SET kind = $2, name = $3, number = $4, expansion_code = $5, last_seen_run = $6,
+                       evolve_marker = $7, img_src = $8, hp = $9, weak_point = $10, resist = $11,
+                       escape = $12, expansion_symbol = $13, energy = $14, artist = NULLIF($15, ''),
+                       regulation_mark = $17
+                   RETURNING code
                    ",
             card.code,
             card.kind,
             card.name,
             card.number,
-            card.set_code
+            card.set_code,
+            self.current_run.id(),
+            card.evolve_marker,
+            card.img_src,
+            card.hp,
+            card.weak_point,
+            card.resist,
+            card.escape,
+            card.expansion_symbol,
+            card.energy,
+            card.artist,
+            card.region.to_string(),
+            card.regulation_mark
+        )
+        .fetch_one(&mut *tx)
+        .await?
+        .code;
+        tx.commit().await?;
+        let old_skills = self.get_ptcg_skills(&code, card.region).await?;
+        self.replace_ptcg_skills(&code, card.region, &card.skills)
+            .await?;
+        let card_key = card_key(&code, card.region);
+        let Some(old) = old else {
+            self.current_run.record(Upsert::Inserted);
+            self.changelog().record_new(Game::Ptcg, &card_key).await?;
+            return Ok(code);
+        };
+        let changes = diff(
+            &[
+                ("name", Some(old.name)),
+                ("kind", Some(old.kind)),
+                ("number", Some(old.number)),
+                ("expansion_code", Some(old.expansion_code)),
+                ("evolve_marker", old.evolve_marker),
+                ("img_src", old.img_src),
+                ("hp", old.hp),
+                ("weak_point", old.weak_point),
+                ("resist", old.resist),
+                ("escape", old.escape),
+                ("expansion_symbol", old.expansion_symbol),
+                ("energy", old.energy),
+                ("artist", old.artist),
+                ("regulation_mark", old.regulation_mark),
+                ("skills", skills_summary(&old_skills)),
+            ],
+            &[
+                ("name", Some(card.name.clone())),
+                ("kind", Some(card.kind.clone())),
+                ("number", card.number.clone()),
+                ("expansion_code", card.set_code.clone()),
+                ("evolve_marker", card.evolve_marker.clone()),
+                ("img_src", card.img_src.clone()),
+                ("hp", card.hp.clone()),
+                ("weak_point", card.weak_point.clone()),
+                ("resist", card.resist.clone()),
+                ("escape", card.escape.clone()),
+                ("expansion_symbol", card.expansion_symbol.clone()),
+                ("energy", card.energy.clone()),
+                (
+                    "artist",
+                    Some(card.artist.clone()).filter(|a| !a.is_empty()),
+                ),
+                ("regulation_mark", card.regulation_mark.clone()),
+                ("skills", skills_summary(&card.skills)),
+            ],
+        );
+        if changes.is_empty() {
+            self.current_run.record(Upsert::Unchanged);
+        } else {
+            self.current_run.record(Upsert::Updated);
+            self.changelog()
+                .record_changed(Game::Ptcg, &card_key, &changes)
+                .await?;
+        }
+        Ok(code)
+    }
+    pub async fn get_ptcg_skills(
+        &self,
+        code: &str,
+        region: PtcgRegion,
+    ) -> Result<Vec<PtcgSkill>, RepositoryError> {
+        let records = sqlx::query!(
+            r#"
+            SELECT s.kind, s.name, s.damage, s.effect,
+                COALESCE(
+                    ARRAY_AGG(c.energy ORDER BY c.position) FILTER (WHERE c.energy IS NOT NULL),
+                    '{}'
+                ) AS "cost!"
+            FROM pokemon_trainer_skill s
+            LEFT JOIN pokemon_trainer_skill_cost c ON c.skill_id = s.id
+            WHERE s.code = $1 AND s.region = $2
+            GROUP BY s.id
+            ORDER BY s.position
+            "#,
+            code,
+            region.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                Some(PtcgSkill {
+                    kind: record.kind.parse().ok()?,
+                    name: record.name,
+                    cost: record.cost,
+                    damage: record.damage,
+                    effect: record.effect,
+                })
+            })
+            .collect())
+    }
+    async fn replace_ptcg_skills(
+        &self,
+        code: &str,
+        region: PtcgRegion,
+        skills: &[PtcgSkill],
+    ) -> Result<(), RepositoryError> {
+        let region = region.to_string();
+        let mut tx = self.pool.begin().await?;
+        sqlx::query!(
+            "DELETE FROM pokemon_trainer_skill WHERE code = $1 AND region = $2",
+            code,
+            region
+        )
+        .execute(&mut *tx)
+        .await?;
+        for (position, skill) in skills.iter().enumerate() {
+            let skill_id = sqlx::query_scalar!(
+                "
+                INSERT INTO pokemon_trainer_skill(code, position, kind, name, damage, effect, region)
+                VALUES($1, $2, $3, $4, $5, $6, $7)
+                RETURNING id
+                ",
+                code,
+                position as i32,
+                skill.kind.to_string(),
+                skill.name,
+                skill.damage,
+                skill.effect,
+                region
+            )
+            .fetch_one(&mut *tx)
+            .await?;
+            for (position, energy) in skill.cost.iter().enumerate() {
+                sqlx::query!(
+                    "INSERT INTO pokemon_trainer_skill_cost(skill_id, position, energy) VALUES($1, $2, $3)",
+                    skill_id,
+                    position as i32,
+                    energy
+                )
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+    pub async fn get_ptcg_rotations(&self) -> Result<Vec<Rotation>, RepositoryError> {
+        let records = sqlx::query!("SELECT format, effective_date, oldest_mark FROM ptcg_rotation")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(records
+            .into_iter()
+            .filter_map(|record| {
+                Some(Rotation {
+                    format: record.format.parse().ok()?,
+                    effective_date: record.effective_date,
+                    oldest_mark: record.oldest_mark,
+                })
+            })
+            .collect())
+    }
+    pub async fn upsert_ptcg_rotation(&self, rotation: &Rotation) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+            INSERT INTO ptcg_rotation(format, effective_date, oldest_mark) VALUES($1, $2, $3)
+            ON CONFLICT(format, effective_date) DO UPDATE SET oldest_mark = $3
+            ",
+            rotation.format.to_string(),
+            rotation.effective_date,
+            rotation.oldest_mark
         )
         .execute(&self.pool)
-        .await
-        .unwrap();
+        .await?;
+        Ok(())
     }
-    pub fn find_ptcg_expansion(&self) -> BoxStream<Result<PtcgExpansion, RepositoryError>> {
-        sqlx::query_as!(
-            PtcgExpansion,
-            "SELECT code, series, name, release_date FROM pokemon_trainer_expansion"
+    pub fn find_ptcg_expansion(
+        &self,
+        region: PtcgRegion,
+    ) -> BoxStream<'_, Result<PtcgExpansion, RepositoryError>> {
+        sqlx::query!(
+            "SELECT code, series, name, release_date FROM pokemon_trainer_expansion WHERE region = $1",
+            region.to_string()
         )
         .fetch(&self.pool)
+        .map_ok(move |record| PtcgExpansion {
+            code: record.code,
+            series: record.series,
+            name: record.name,
+            release_date: record.release_date,
+            region,
+        })
         .map_err(RepositoryError::from)
         .boxed()
     }
 
-    pub(crate) fn get_all_pokemon_trainer_printing(&self) -> BoxStream<PokemonCard> {
+    // every region when None
+    pub(crate) fn get_all_pokemon_trainer_printing(
+        &self,
+        region: Option<PtcgRegion>,
+    ) -> BoxStream<'_, PokemonCard> {
         sqlx::query!(
-            "SELECT
-            p.code as id,
-            p.name as name,
-            p.number as number,
+            r#"SELECT
+            p.code as "id!",
+            p.name as "name!",
+            p.number as "number!",
             NULL::bigint as sale_price,
             p.rarity as rarity,
-            e.code as set_id,
-            e.name as set_name,
-            e.code as set_ref,
-            NULL as remark
+            e.code as "set_id?",
+            e.name as "set_name?",
+            e.code as "set_ref!",
+            NULL as remark,
+            p.kind as "kind!", p.evolve_marker, p.img_src, p.hp, p.weak_point, p.resist, p.escape,
+            p.expansion_symbol, p.energy, p.artist, p.regulation_mark, p.region AS "region!"
             FROM pokemon_trainer_printing p
-            LEFT JOIN pokemon_trainer_expansion e ON p.expansion_code = e.code"
+            LEFT JOIN pokemon_trainer_expansion e ON p.expansion_code = e.code AND p.region = e.region
+            WHERE $1::TEXT IS NULL OR p.region = $1"#,
+            region.map(|r| r.to_string())
         )
         .fetch(&self.pool)
         .filter_map(|r| async {
@@ -182,6 +498,20 @@ impl PokemonRepository {
                 rarity: record.rarity,
                 remark: record.remark,
                 last_fetched_at: LastFetchedAt::default(),
+                region: record.region.parse().unwrap_or_default(),
+                attributes: PtcgPrintingAttributes {
+                    kind: Some(record.kind),
+                    evolve_marker: record.evolve_marker,
+                    img_src: record.img_src,
+                    hp: record.hp,
+                    weak_point: record.weak_point,
+                    resist: record.resist,
+                    escape: record.escape,
+                    expansion_symbol: record.expansion_symbol,
+                    energy: record.energy,
+                    artist: record.artist,
+                    regulation_mark: record.regulation_mark,
+                },
             })
         })
         .boxed()
@@ -191,7 +521,7 @@ impl PokemonRepository {
         detail: &TcgCollectorCardDetail,
     ) -> Result<bool, RepositoryError> {
         let r = sqlx::query!(
-            "SELECT * FROM pokemon_trainer_printing WHERE number = $1 AND expansion_code = $2",
+            "SELECT * FROM pokemon_trainer_printing WHERE number = $1 AND expansion_code = $2 AND region = 'tw'",
             detail.number,
             detail.exp_code
         )
@@ -201,3 +531,18 @@ impl PokemonRepository {
         Ok(r)
     }
 }
+
+// region is part of the card_changes and card_history key, codes repeat across regions
+fn card_key(code: &str, region: PtcgRegion) -> String {
+    format!("{code}|{region}")
+}
+
+fn skills_summary(skills: &[PtcgSkill]) -> Option<String> {
+    (!skills.is_empty()).then(|| {
+        skills
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("\n")
+    })
+}
@@ -0,0 +1,171 @@
+use std::sync::{
+    atomic::{AtomicI32, Ordering},
+    OnceLock,
+};
+
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::domain::Game;
+
+use super::RepositoryError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Upsert {
+    Inserted,
+    Updated,
+    Unchanged,
+}
+
+impl Upsert {
+    // from an upsert that compares the row it wrote with the one it replaced
+    pub fn from_flags(inserted: bool, changed: bool) -> Self {
+        match (inserted, changed) {
+            (true, _) => Upsert::Inserted,
+            (false, true) => Upsert::Updated,
+            (false, false) => Upsert::Unchanged,
+        }
+    }
+}
+
+// The scrape run of this process and its card writes, shared by every clone of the repository
+#[derive(Debug, Default)]
+pub struct CurrentRun {
+    id: OnceLock<i32>,
+    inserted: AtomicI32,
+    updated: AtomicI32,
+    unchanged: AtomicI32,
+}
+
+impl CurrentRun {
+    pub fn id(&self) -> Option<i32> {
+        self.id.get().copied()
+    }
+    pub fn set_id(&self, id: i32) {
+        let _ = self.id.set(id);
+    }
+    pub fn record(&self, upsert: Upsert) {
+        self.add(upsert, 1);
+    }
+    pub fn add(&self, upsert: Upsert, count: i32) {
+        let counter = match upsert {
+            Upsert::Inserted => &self.inserted,
+            Upsert::Updated => &self.updated,
+            Upsert::Unchanged => &self.unchanged,
+        };
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+    pub fn get(&self, upsert: Upsert) -> i32 {
+        match upsert {
+            Upsert::Inserted => self.inserted.load(Ordering::Relaxed),
+            Upsert::Updated => self.updated.load(Ordering::Relaxed),
+            Upsert::Unchanged => self.unchanged.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RunRecord {
+    pub id: i32,
+    pub game: String,
+    pub command: String,
+    pub status: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub pages_fetched: i32,
+    pub cards_inserted: i32,
+    pub cards_updated: i32,
+    pub cards_unchanged: i32,
+    pub error_count: i32,
+    pub error_message: Option<String>,
+}
+
+pub struct RunRepository {
+    pub(crate) pool: Pool<Postgres>,
+}
+
+impl RunRepository {
+    pub async fn start(&self, game: Game, command: &str) -> Result<i32, RepositoryError> {
+        let record = sqlx::query!(
+            "INSERT INTO scrape_runs(game, command) VALUES($1, $2) RETURNING id",
+            game.to_string(),
+            command
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(record.id)
+    }
+    // error_count is taken from the scrape_errors recorded by this run
+    pub async fn finish(
+        &self,
+        id: i32,
+        pages_fetched: i32,
+        current_run: &CurrentRun,
+        error_message: Option<String>,
+    ) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+            UPDATE scrape_runs r
+            SET status = CASE WHEN $6::TEXT IS NULL THEN 'succeeded' ELSE 'failed' END,
+                finished_at = NOW(),
+                pages_fetched = $2,
+                cards_inserted = $3,
+                cards_updated = $4,
+                cards_unchanged = $5,
+                error_count = (SELECT COUNT(*) FROM scrape_errors e WHERE e.run_id = r.id),
+                error_message = $6
+            WHERE id = $1
+            ",
+            id,
+            pages_fetched,
+            current_run.get(Upsert::Inserted),
+            current_run.get(Upsert::Updated),
+            current_run.get(Upsert::Unchanged),
+            error_message
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    pub async fn list(
+        &self,
+        game: Option<Game>,
+        limit: i64,
+    ) -> Result<Vec<RunRecord>, RepositoryError> {
+        let records = sqlx::query_as!(
+            RunRecord,
+            r#"
+            SELECT id, game, command, status,
+                started_at::TEXT AS "started_at!", finished_at::TEXT AS finished_at,
+                pages_fetched, cards_inserted, cards_updated, cards_unchanged,
+                error_count, error_message
+            FROM scrape_runs
+            WHERE $1::TEXT IS NULL OR game = $1
+            ORDER BY started_at DESC
+            LIMIT $2
+            "#,
+            game.map(|g| g.to_string()),
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(records)
+    }
+    pub async fn find(&self, id: i32) -> Result<Option<RunRecord>, RepositoryError> {
+        let record = sqlx::query_as!(
+            RunRecord,
+            r#"
+            SELECT id, game, command, status,
+                started_at::TEXT AS "started_at!", finished_at::TEXT AS finished_at,
+                pages_fetched, cards_inserted, cards_updated, cards_unchanged,
+                error_count, error_message
+            FROM scrape_runs
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(record)
+    }
+}
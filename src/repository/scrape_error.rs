@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{domain::Game, error::CardFailure};
+
+use super::{run::CurrentRun, RepositoryError};
+
+#[derive(Debug, Serialize)]
+pub struct ScrapeErrorRecord {
+    pub id: i32,
+    pub game: String,
+    pub url: String,
+    pub card_identifier: Option<String>,
+    pub kind: String,
+    pub message: String,
+    pub created_at: String,
+    pub resolved_at: Option<String>,
+}
+
+pub struct ScrapeErrorRepository {
+    pub(crate) pool: Pool<Postgres>,
+    pub(crate) current_run: Arc<CurrentRun>,
+}
+
+impl ScrapeErrorRepository {
+    pub async fn record(&self, game: Game, failure: &CardFailure) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "
+            INSERT INTO scrape_errors(game, url, card_identifier, kind, message, snippet, run_id)
+            VALUES($1, $2, $3, $4, $5, $6, $7)
+            ",
+            game.to_string(),
+            failure.url,
+            failure.card_identifier,
+            failure.kind,
+            failure.message,
+            failure.snippet,
+            self.current_run.id(),
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+    pub async fn list(
+        &self,
+        game: Option<Game>,
+        include_resolved: bool,
+    ) -> Result<Vec<ScrapeErrorRecord>, RepositoryError> {
+        let records = sqlx::query_as!(
+            ScrapeErrorRecord,
+            r#"
+            SELECT id, game, url, card_identifier, kind, message,
+                created_at::TEXT AS "created_at!", resolved_at::TEXT AS resolved_at
+            FROM scrape_errors
+            WHERE ($1::TEXT IS NULL OR game = $1) AND ($2 OR resolved_at IS NULL)
+            ORDER BY id
+            "#,
+            game.map(|g| g.to_string()),
+            include_resolved,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(records)
+    }
+    pub async fn resolve(&self, ids: &[i32]) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "UPDATE scrape_errors SET resolved_at = NOW() WHERE id = ANY($1)",
+            ids
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
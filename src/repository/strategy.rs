@@ -0,0 +1,156 @@
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::domain::PtcgRegion;
+
+use super::RepositoryError;
+
+#[derive(Debug)]
+pub struct StoredStrategy {
+    pub id: i32,
+    pub exp_code: String,
+    pub version: i32,
+    pub name: String,
+    pub sources: serde_json::Value,
+    pub author: Option<String>,
+}
+
+// one version of an expansion's strategy and the rows it last wrote
+#[derive(Debug, Serialize)]
+pub struct StrategyVersion {
+    pub version: i32,
+    pub name: String,
+    pub author: Option<String>,
+    pub created_at: String,
+    pub applied_at: Option<String>,
+    pub pokewiki_rows: i64,
+    pub printing_rows: i64,
+    pub sources: String,
+}
+
+pub struct StrategyRepository {
+    pub(crate) pool: Pool<Postgres>,
+}
+
+impl StrategyRepository {
+    // a new version unless the sources equal the latest one's; None when unchanged
+    pub async fn save(
+        &self,
+        exp_code: &str,
+        region: PtcgRegion,
+        name: &str,
+        sources: &serde_json::Value,
+        author: Option<&str>,
+    ) -> Result<Option<i32>, RepositoryError> {
+        let mut tx = self.pool.begin().await?;
+        let latest = sqlx::query!(
+            "
+            SELECT version, sources FROM ptcg_strategy
+            WHERE exp_code = $1 AND region = $2
+            ORDER BY version DESC
+            LIMIT 1
+            FOR UPDATE
+            ",
+            exp_code,
+            region.to_string()
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if latest.as_ref().is_some_and(|l| &l.sources == sources) {
+            return Ok(None);
+        }
+        let version = latest.map_or(1, |l| l.version + 1);
+        sqlx::query!(
+            "
+            INSERT INTO ptcg_strategy(exp_code, region, version, name, sources, author)
+            VALUES($1, $2, $3, $4, $5, $6)
+            ",
+            exp_code,
+            region.to_string(),
+            version,
+            name,
+            sources,
+            author
+        )
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(version))
+    }
+    // the latest version when version is None
+    pub async fn find(
+        &self,
+        exp_code: &str,
+        region: PtcgRegion,
+        version: Option<i32>,
+    ) -> Result<Option<StoredStrategy>, RepositoryError> {
+        let strategy = sqlx::query_as!(
+            StoredStrategy,
+            "
+            SELECT id, exp_code, version, name, sources, author
+            FROM ptcg_strategy
+            WHERE exp_code = $1 AND region = $2 AND ($3::INT IS NULL OR version = $3)
+            ORDER BY version DESC
+            LIMIT 1
+            ",
+            exp_code,
+            region.to_string(),
+            version
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(strategy)
+    }
+    // the latest version of every expansion
+    pub async fn list_latest(
+        &self,
+        region: PtcgRegion,
+    ) -> Result<Vec<StoredStrategy>, RepositoryError> {
+        let strategies = sqlx::query_as!(
+            StoredStrategy,
+            "
+            SELECT DISTINCT ON (exp_code) id, exp_code, version, name, sources, author
+            FROM ptcg_strategy
+            WHERE region = $1
+            ORDER BY exp_code, version DESC
+            ",
+            region.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(strategies)
+    }
+    pub async fn versions(
+        &self,
+        exp_code: &str,
+        region: PtcgRegion,
+    ) -> Result<Vec<StrategyVersion>, RepositoryError> {
+        let versions = sqlx::query_as!(
+            StrategyVersion,
+            r#"
+            SELECT s.version, s.name, s.author,
+                s.created_at::TEXT AS "created_at!", s.applied_at::TEXT AS applied_at,
+                (SELECT COUNT(*) FROM pokewiki w WHERE w.strategy_id = s.id) AS "pokewiki_rows!",
+                (SELECT COUNT(*) FROM pokemon_trainer_printing p WHERE p.strategy_id = s.id) AS "printing_rows!",
+                s.sources::TEXT AS "sources!"
+            FROM ptcg_strategy s
+            WHERE s.exp_code = $1 AND s.region = $2
+            ORDER BY s.version
+            "#,
+            exp_code,
+            region.to_string()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(versions)
+    }
+    pub async fn applied(&self, id: i32) -> Result<(), RepositoryError> {
+        sqlx::query!(
+            "UPDATE ptcg_strategy SET applied_at = NOW() WHERE id = $1",
+            id
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+// PTCG formats whose card pool is bounded by regulation marks
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum, strum::Display, strum::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum PtcgFormat {
+    Standard,
+    Expanded,
+}
+
+// widest format a printing can be played in
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum PtcgLegality {
+    Standard,
+    Expanded,
+    // no regulation mark, or older than the Expanded cutoff
+    Unlimited,
+}
+
+// from effective_date on, marks from oldest_mark up are legal in format
+#[derive(Debug, Clone)]
+pub struct Rotation {
+    pub format: PtcgFormat,
+    pub effective_date: NaiveDate,
+    pub oldest_mark: String,
+}
+
+pub struct Rotations(Vec<Rotation>);
+
+impl Rotations {
+    pub fn new(mut rotations: Vec<Rotation>) -> Self {
+        rotations.sort_by_key(|r| r.effective_date);
+        Self(rotations)
+    }
+    fn oldest_mark(&self, format: PtcgFormat, on: NaiveDate) -> Option<&str> {
+        self.0
+            .iter()
+            .rev()
+            .find(|r| r.format == format && r.effective_date <= on)
+            .map(|r| r.oldest_mark.as_str())
+    }
+    // marks are single letters, later letters are newer
+    pub fn legality(&self, mark: Option<&str>, on: NaiveDate) -> PtcgLegality {
+        let Some(mark) = mark else {
+            return PtcgLegality::Unlimited;
+        };
+        let legal_in = |format| {
+            self.oldest_mark(format, on)
+                .is_some_and(|oldest| mark >= oldest)
+        };
+        if legal_in(PtcgFormat::Standard) {
+            PtcgLegality::Standard
+        } else if legal_in(PtcgFormat::Expanded) {
+            PtcgLegality::Expanded
+        } else {
+            PtcgLegality::Unlimited
+        }
+    }
+    // first announced rotation after `on` that drops a Standard-legal mark
+    pub fn rotates_out(&self, mark: Option<&str>, on: NaiveDate) -> Option<NaiveDate> {
+        if self.legality(mark, on) != PtcgLegality::Standard {
+            return None;
+        }
+        self.0
+            .iter()
+            .filter(|r| r.format == PtcgFormat::Standard && r.effective_date > on)
+            .find(|r| self.legality(mark, r.effective_date) != PtcgLegality::Standard)
+            .map(|r| r.effective_date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rotation(format: PtcgFormat, date: &str, oldest_mark: &str) -> Rotation {
+        Rotation {
+            format,
+            effective_date: date.parse().unwrap(),
+            oldest_mark: oldest_mark.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_legality() {
+        let rotations = Rotations::new(vec![
+            rotation(PtcgFormat::Standard, "2025-01-24", "F"),
+            rotation(PtcgFormat::Standard, "2024-01-26", "E"),
+            rotation(PtcgFormat::Expanded, "2024-01-26", "A"),
+        ]);
+        let on = "2024-06-01".parse().unwrap();
+        assert_eq!(rotations.legality(Some("G"), on), PtcgLegality::Standard);
+        assert_eq!(rotations.legality(Some("D"), on), PtcgLegality::Expanded);
+        assert_eq!(rotations.legality(None, on), PtcgLegality::Unlimited);
+        assert_eq!(
+            rotations.rotates_out(Some("E"), on),
+            Some("2025-01-24".parse().unwrap())
+        );
+        assert_eq!(rotations.rotates_out(Some("F"), on), None);
+        assert_eq!(rotations.rotates_out(Some("D"), on), None);
+    }
+}
@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Deserializer};
+
+use crate::domain::{Game, PtcgRegion, YugiohLocale};
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, strum::Display)]
+#[serde(rename_all = "kebab-case")]
+#[strum(serialize_all = "kebab-case")]
+pub enum Stage {
+    // the generic expansion -> card -> detail pipeline, any game
+    Pipeline,
+    Prepare,
+    ForbiddenLimited,
+    Card,
+    Rarity,
+}
+
+// what the daemon runs for an entry, `Application::run_stage` matches on it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Task {
+    Pipeline,
+    PtcgPrepare,
+    YugiohForbiddenLimited,
+    PtcgJpCard,
+    PtcgJpRarity,
+}
+
+impl Task {
+    // the stages each game has
+    fn of(game: Game, stage: Stage) -> Option<Self> {
+        let task = match (game, stage) {
+            (_, Stage::Pipeline) => Task::Pipeline,
+            (Game::Ptcg, Stage::Prepare) => Task::PtcgPrepare,
+            (Game::Yugioh, Stage::ForbiddenLimited) => Task::YugiohForbiddenLimited,
+            (Game::PtcgJp, Stage::Card) => Task::PtcgJpCard,
+            (Game::PtcgJp, Stage::Rarity) => Task::PtcgJpRarity,
+            _ => return None,
+        };
+        Some(task)
+    }
+}
+
+#[derive(Deserialize)]
+struct RawScheduleEntry {
+    game: Game,
+    stage: Stage,
+    #[serde(deserialize_with = "deserialize_cron")]
+    cron: cron::Schedule,
+    #[serde(default)]
+    locale: YugiohLocale,
+    #[serde(default)]
+    region: PtcgRegion,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(try_from = "RawScheduleEntry")]
+pub struct ScheduleEntry {
+    pub game: Game,
+    pub stage: Stage,
+    pub task: Task,
+    // sec min hour day-of-month month day-of-week, e.g. "0 0 2 * * *" for 02:00 every night
+    pub cron: cron::Schedule,
+    // database of the yugioh stages
+    pub locale: YugiohLocale,
+    // catalogue of the ptcg stages
+    pub region: PtcgRegion,
+}
+
+impl TryFrom<RawScheduleEntry> for ScheduleEntry {
+    type Error = String;
+
+    fn try_from(raw: RawScheduleEntry) -> Result<Self, Self::Error> {
+        let task = Task::of(raw.game, raw.stage)
+            .ok_or_else(|| format!("{} has no {} stage", raw.game, raw.stage))?;
+        Ok(Self {
+            game: raw.game,
+            stage: raw.stage,
+            task,
+            cron: raw.cron,
+            locale: raw.locale,
+            region: raw.region,
+        })
+    }
+}
+
+impl ScheduleEntry {
+    // same name as the matching CLI command, so runs of both line up in scrape_runs
+    pub fn command(&self) -> String {
+        let command = match self.stage {
+            Stage::Pipeline => format!("scrape {}", self.game),
+            stage => {
+                let game = self.game.to_possible_value().unwrap();
+                format!("{} {stage}", game.get_name())
+            }
+        };
+        match self.game {
+            Game::Yugioh => self.locale.qualify(command),
+            Game::Ptcg => self.region.qualify(command),
+            _ => command,
+        }
+    }
+}
+
+fn deserialize_cron<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<cron::Schedule, D::Error> {
+    let expression = String::deserialize(deserializer)?;
+    cron::Schedule::from_str(&expression).map_err(serde::de::Error::custom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule() {
+        let json = r#"
+  [
+    { "game": "ws", "stage": "pipeline", "cron": "0 0 2 * * *" },
+    { "game": "ptcg_jp", "stage": "card", "cron": "0 0 3 * * Sun" },
+    { "game": "yugioh", "stage": "pipeline", "cron": "0 0 4 * * *" },
+    { "game": "yugioh", "stage": "forbidden-limited", "cron": "0 0 5 * * *", "locale": "ko" },
+    { "game": "ptcg", "stage": "pipeline", "cron": "0 0 6 * * *", "region": "th" }
+  ]
+        "#;
+        let entries: Vec<ScheduleEntry> = serde_json::from_str(json).unwrap();
+        assert_eq!(entries[1].task, Task::PtcgJpCard);
+        assert_eq!(entries[0].command(), "scrape ws");
+        assert_eq!(entries[1].command(), "ptcg-jp card");
+        assert_eq!(entries[2].command(), "scrape yugioh");
+        assert_eq!(entries[3].command(), "yugioh forbidden-limited --locale ko");
+        assert_eq!(entries[4].command(), "scrape ptcg --region th");
+    }
+    #[test]
+    fn test_unsupported_stage() {
+        let json = r#"{ "game": "ws", "stage": "forbidden-limited", "cron": "0 0 2 * * *" }"#;
+        let err = serde_json::from_str::<ScheduleEntry>(json).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("ws has no forbidden-limited stage"));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{
+    domain::Game,
+    error::{CardFailure, Error},
+};
+
+// One game's catalogue as seen by `application::pipeline::Pipeline`:
+// expansions -> card references in an expansion -> card detail (+ image)
+pub(crate) trait CardSource {
+    type Expansion;
+    type CardRef;
+    type Card;
+
+    fn game(&self) -> Game;
+    async fn expansions(&self) -> Result<Vec<Self::Expansion>, Error>;
+    // what the pipeline checkpoint saves after an expansion is done
+    fn expansion_key(&self, expansion: &Self::Expansion) -> String;
+    async fn card_refs(
+        &self,
+        expansion: &Self::Expansion,
+    ) -> Result<Vec<Result<Self::CardRef, CardFailure>>, Error>;
+    async fn card_detail(&self, card_ref: Self::CardRef) -> Result<Self::Card, Error>;
+    fn card_url(&self, card_ref: &Self::CardRef) -> String;
+    // what `ScrapeErrors::requeue` needs besides the url to fetch the card again
+    fn card_identifier(&self, _card_ref: &Self::CardRef) -> Option<String> {
+        None
+    }
+    fn image_url(&self, card: &Self::Card) -> Option<url::Url>;
+    // where the image is stored, <game>/<last segment of the image url> unless overridden
+    fn image_name(&self, _card: &Self::Card, image_url: &url::Url) -> String {
+        let file_name = image_url
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or_default();
+        format!("{}/{file_name}", self.game())
+    }
+}
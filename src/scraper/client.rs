@@ -0,0 +1,237 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, ACCEPT_LANGUAGE, RETRY_AFTER},
+    Response, StatusCode,
+};
+use tokio::time::{sleep, sleep_until, Instant};
+use tracing::warn;
+
+use super::{env_parse, scraper_error::ScraperError};
+use crate::{error::Error, repository::archive::ArchiveRepository};
+
+const DEFAULT_USER_AGENT: &str = concat!("tcg-scraper/", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug, Clone)]
+pub struct HttpConfig {
+    pub user_agent: String,
+    pub accept_language: String,
+    pub timeout: Duration,
+    pub max_retries: u32,
+    pub backoff_base: Duration,
+    // minimum gap between two requests to the same host
+    pub default_interval: Duration,
+    pub host_intervals: HashMap<String, Duration>,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        let host_intervals = HashMap::from([
+            (
+                "asia.pokemon-card.com".to_string(),
+                Duration::from_millis(1500),
+            ),
+            ("ws-tcg.com".to_string(), Duration::from_millis(1000)),
+        ]);
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            accept_language: "ja,zh-TW;q=0.9,en;q=0.8".to_string(),
+            timeout: Duration::from_secs(30),
+            max_retries: 5,
+            backoff_base: Duration::from_secs(2),
+            default_interval: Duration::from_millis(500),
+            host_intervals,
+        }
+    }
+}
+
+impl HttpConfig {
+    // SCRAPER_HOST_INTERVALS example: asia.pokemon-card.com=2000,ws-tcg.com=1000 (milliseconds)
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(user_agent) = std::env::var("SCRAPER_USER_AGENT") {
+            config.user_agent = user_agent;
+        }
+        if let Ok(accept_language) = std::env::var("SCRAPER_ACCEPT_LANGUAGE") {
+            config.accept_language = accept_language;
+        }
+        if let Some(secs) = env_parse("SCRAPER_TIMEOUT_SECS") {
+            config.timeout = Duration::from_secs(secs);
+        }
+        if let Some(retries) = env_parse("SCRAPER_MAX_RETRIES") {
+            config.max_retries = retries;
+        }
+        if let Some(millis) = env_parse("SCRAPER_DEFAULT_INTERVAL_MS") {
+            config.default_interval = Duration::from_millis(millis);
+        }
+        if let Ok(intervals) = std::env::var("SCRAPER_HOST_INTERVALS") {
+            for entry in intervals.split(',') {
+                let Some((host, millis)) = entry.split_once('=') else {
+                    continue;
+                };
+                if let Ok(millis) = millis.trim().parse() {
+                    config
+                        .host_intervals
+                        .insert(host.trim().to_string(), Duration::from_millis(millis));
+                }
+            }
+        }
+        config
+    }
+    fn interval_for(&self, host: &str) -> Duration {
+        self.host_intervals
+            .get(host)
+            .copied()
+            .unwrap_or(self.default_interval)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FetchMode {
+    Live,
+    // parse from page_archive only, never touch the network
+    Replay,
+}
+
+#[derive(Clone)]
+pub struct HttpClient {
+    inner: reqwest::Client,
+    config: Arc<HttpConfig>,
+    next_slots: Arc<Mutex<HashMap<String, Instant>>>,
+    archive: Option<ArchiveRepository>,
+    mode: FetchMode,
+    pages_fetched: Arc<AtomicI32>,
+}
+
+impl HttpClient {
+    pub fn new(config: HttpConfig) -> Result<Self, Error> {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            ACCEPT_LANGUAGE,
+            HeaderValue::from_str(&config.accept_language)
+                .unwrap_or(HeaderValue::from_static("ja")),
+        );
+        let inner = reqwest::Client::builder()
+            .user_agent(&config.user_agent)
+            .default_headers(headers)
+            .timeout(config.timeout)
+            .build()?;
+        Ok(Self {
+            inner,
+            config: Arc::new(config),
+            next_slots: Arc::new(Mutex::new(HashMap::new())),
+            archive: None,
+            mode: FetchMode::Live,
+            pages_fetched: Arc::default(),
+        })
+    }
+    pub fn with_archive(mut self, archive: ArchiveRepository, mode: FetchMode) -> Self {
+        self.archive = Some(archive);
+        self.mode = mode;
+        self
+    }
+    pub fn is_replay(&self) -> bool {
+        self.mode == FetchMode::Replay
+    }
+    // shares throttling and archive, but counts pages separately
+    pub fn for_new_run(&self) -> Self {
+        Self {
+            pages_fetched: Arc::default(),
+            ..self.clone()
+        }
+    }
+    // page sources handed to scrapers, live or replayed
+    pub fn pages_fetched(&self) -> i32 {
+        self.pages_fetched.load(Ordering::Relaxed)
+    }
+    pub async fn get_source(&self, url: &str) -> Result<String, Error> {
+        if self.is_replay() {
+            return self.replayed(url).await;
+        }
+        let source = self.get(url).await?.text().await?;
+        self.archive(url, &source).await?;
+        Ok(source)
+    }
+    pub async fn replayed(&self, url: &str) -> Result<String, Error> {
+        let archive = self
+            .archive
+            .as_ref()
+            .ok_or_else(|| ScraperError::NotArchived(url.to_string()))?;
+        let source = archive
+            .latest_page(url)
+            .await?
+            .ok_or_else(|| ScraperError::NotArchived(url.to_string()))?;
+        self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+        Ok(source)
+    }
+    pub async fn archive(&self, url: &str, source: &str) -> Result<(), Error> {
+        self.pages_fetched.fetch_add(1, Ordering::Relaxed);
+        if let Some(archive) = &self.archive {
+            archive.save_page(url, source).await?;
+        }
+        Ok(())
+    }
+    pub async fn get(&self, url: &str) -> Result<Response, Error> {
+        if self.is_replay() {
+            return Err(ScraperError::ReplayNetwork(url.to_string()).into());
+        }
+        let parsed = url::Url::parse(url)?;
+        let host = parsed.host_str().unwrap_or_default().to_string();
+        let mut attempt = 0;
+        loop {
+            self.throttle(&host).await;
+            let retry_after = match self.inner.get(parsed.clone()).send().await {
+                Ok(resp) if is_retryable(resp.status()) && attempt < self.config.max_retries => {
+                    warn!("{url} responded {}, attempt {attempt}", resp.status());
+                    retry_after(&resp)
+                }
+                Ok(resp) => return Ok(resp.error_for_status()?),
+                Err(err) if is_transient(&err) && attempt < self.config.max_retries => {
+                    warn!("{url} failed: {err}, attempt {attempt}");
+                    None
+                }
+                Err(err) => return Err(err.into()),
+            };
+            let backoff = self.config.backoff_base * 2u32.pow(attempt);
+            sleep(retry_after.unwrap_or(backoff).max(backoff)).await;
+            attempt += 1;
+        }
+    }
+    async fn throttle(&self, host: &str) {
+        let interval = self.config.interval_for(host);
+        let slot = {
+            let mut next_slots = self.next_slots.lock().unwrap();
+            let now = Instant::now();
+            let slot = next_slots.get(host).copied().unwrap_or(now).max(now);
+            next_slots.insert(host.to_string(), slot + interval);
+            slot
+        };
+        sleep_until(slot).await;
+    }
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn is_transient(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_connect() || err.is_request()
+}
+
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let secs = resp
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()?;
+    Some(Duration::from_secs(secs))
+}
@@ -1,15 +1,22 @@
 use scraper::ElementRef;
 
-use crate::error::Error;
-
+pub mod card_source;
+pub mod client;
 pub mod one_piece;
+pub mod page;
 pub mod pokemon_wiki;
 pub mod ptcg;
 pub mod scraper_error;
 pub mod tcg_collector;
+pub mod webdriver;
 pub mod ws;
 pub mod yugioh;
 
+// None when the variable is unset or does not parse, the caller keeps its default
+fn env_parse<T: std::str::FromStr>(key: &str) -> Option<T> {
+    std::env::var(key).ok()?.parse().ok()
+}
+
 pub(crate) trait Inner {
     fn inner_trim(&self) -> String;
     fn inner_lowercase_trim(&self) -> String;
@@ -23,7 +30,3 @@ impl<'a> Inner for ElementRef<'a> {
         self.inner_html().trim().to_lowercase()
     }
 }
-
-pub async fn get_source(url: &str) -> Result<String, Error> {
-    Ok(reqwest::Client::new().get(url).send().await?.text().await?)
-}
@@ -1,28 +1,26 @@
 use std::path::Path;
 
-use scraper::Selector;
+use scraper::{ElementRef, Selector};
 use serde::Deserialize;
 use serde_json::json;
 use strum::AsRefStr;
 
+use super::{card_source::CardSource, client::HttpClient, page::Page, scraper_error::ScraperError};
 use crate::{
-    domain::LastFetchedAt,
-    error::{Error, ErrorCode},
+    domain::{Game, LastFetchedAt},
+    error::{CardFailure, Error, ErrorCode},
 };
 const BASEURL: &str = "https://www.onepiece-cardgame.com";
 
-pub(crate) struct OnePieceScraper {}
+pub(crate) struct OnePieceScraper {
+    pub client: HttpClient,
+}
 impl OnePieceScraper {
     pub(crate) async fn set(&self) -> Result<Vec<String>, Error> {
         let mut results = vec![];
         let series = "550105";
         let url = format!("{}/cardlist/?series={}", BASEURL, series);
-        let source = reqwest::Client::new()
-            .get(&url)
-            .send()
-            .await?
-            .text()
-            .await?;
+        let source = self.client.get_source(&url).await?;
         let document = scraper::Html::parse_document(&source);
         let option_selector = &Selector::parse("#series option").unwrap();
         for option in document.select(option_selector) {
@@ -36,7 +34,7 @@ impl OnePieceScraper {
     pub(crate) async fn products(&self) -> Result<Vec<OnePieceProduct>, Error> {
         let mut results = vec![];
         let url = format!("{}/products", BASEURL);
-        let source = reqwest::Client::new().get(url).send().await?.text().await?;
+        let source = self.client.get_source(&url).await?;
         let document = scraper::Html::parse_document(&source);
         let selector = &Selector::parse(".productsDetail").unwrap();
         for product_detail in document.select(selector) {
@@ -65,75 +63,115 @@ impl OnePieceScraper {
     pub(crate) async fn scrape_cards(
         &self,
         series: &str,
-    ) -> Result<Vec<Result<OnePieceCard, ErrorCode>>, Error> {
-        let mut results = vec![];
+    ) -> Result<Vec<Result<OnePieceCard, CardFailure>>, Error> {
         let url = format!("{}/cardlist/?series={}", BASEURL, series);
-        let source = reqwest::Client::new()
-            .get(&url)
-            .send()
-            .await?
-            .text()
-            .await?;
-        let document = scraper::Html::parse_document(&source);
-        let set_name_selector = &Selector::parse("#series option").unwrap();
-        let set_name = document
-            .select(set_name_selector)
+        let source = self.client.get_source(&url).await?;
+        let page = Page::new(&url, &source);
+        let set_name = page
+            .select(page.root(), "#series option")?
+            .into_iter()
             .find(|e| e.value().attr("selected").is_some())
-            .unwrap()
+            .ok_or_else(|| ScraperError::MissingElement {
+                url: page.url().to_string(),
+                field: "set_name",
+                selector: "#series option[selected]".to_string(),
+            })?
             .inner_html();
-        let dls_selector = Selector::parse("div.resultCol dl").unwrap();
-        let dls = document.select(&dls_selector);
-        for dl in dls {
-            let get_info_selector = &Selector::parse("dd .getInfo").unwrap();
-            let get_info = dl
-                .select(get_info_selector)
-                .next()
-                .unwrap()
-                .text()
-                .nth(1)
-                .unwrap()
-                .trim();
-            let card_name_selector = &Selector::parse("dt .cardName").unwrap();
-            let card_name = dl.select(card_name_selector).next().unwrap().inner_html();
-            let code_selector = &Selector::parse("dt .infoCol span").unwrap();
-            let rarity = dl.select(code_selector).nth(1).unwrap().inner_html();
-            let rarity = serde_json::from_value(json!(&rarity))?;
-            let card_type = dl
-                .select(code_selector)
-                .nth(2)
-                .unwrap()
-                .inner_html()
-                .trim()
-                .to_string();
-            let card_type: OnePieceCardType = serde_json::from_value(json!(&card_type))?;
-            let img_selector = &Selector::parse("dd img").unwrap();
-            let img_src = dl
-                .select(img_selector)
-                .next()
-                .unwrap()
-                .value()
-                .attr("src")
-                .unwrap();
-            let path = Path::new(img_src);
-            let img_src = format!("{}{}", BASEURL, img_src.replace("..", ""));
-            let file_name = path.file_name().unwrap().to_string_lossy().to_string();
-            let (code, _) = file_name.split_once('.').unwrap();
-            let one_piece_card = OnePieceCard {
-                name: card_name,
-                code: code.to_string(),
-                img_src,
-                rarity,
-                get_info: get_info.to_string(),
-                r#type: card_type,
-                set_name: set_name.clone(),
-                last_fetched_at: LastFetchedAt::default(),
-            };
-            results.push(Ok(one_piece_card));
-        }
+        let results = page
+            .select(page.root(), "div.resultCol dl")?
+            .into_iter()
+            .map(|dl| {
+                parse_card(&page, dl, &set_name).map_err(|code| {
+                    let name = page
+                        .find_opt(dl, "dt .cardName")
+                        .ok()
+                        .flatten()
+                        .map(|n| n.inner_html());
+                    CardFailure::new(page.url(), name, code, Some(dl.html()))
+                })
+            })
+            .collect();
         Ok(results)
     }
 }
 
+fn parse_card(page: &Page, dl: ElementRef, set_name: &str) -> Result<OnePieceCard, ErrorCode> {
+    let get_info = page
+        .find(dl, "get_info", "dd .getInfo")?
+        .text()
+        .nth(1)
+        .ok_or_else(|| page.invalid("get_info", ""))?
+        .trim();
+    let card_name = page.find(dl, "name", "dt .cardName")?.inner_html();
+    let info_col = page.select(dl, "dt .infoCol span")?;
+    let rarity = info_col
+        .get(1)
+        .ok_or_else(|| page.invalid("rarity", ""))?
+        .inner_html();
+    let rarity = serde_json::from_value(json!(&rarity)).map_err(|_| ErrorCode::RarityNotExists)?;
+    let card_type = info_col
+        .get(2)
+        .ok_or_else(|| page.invalid("type", ""))?
+        .inner_html()
+        .trim()
+        .to_string();
+    let card_type: OnePieceCardType =
+        serde_json::from_value(json!(&card_type)).map_err(|_| page.invalid("type", &card_type))?;
+    let img_src = page.attr(page.find(dl, "img_src", "dd img")?, "img_src", "src")?;
+    let path = Path::new(img_src);
+    let file_name = path
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .ok_or_else(|| page.invalid("code", img_src))?;
+    let (code, _) = file_name
+        .split_once('.')
+        .ok_or_else(|| page.invalid("code", img_src))?;
+    let img_src = format!("{}{}", BASEURL, img_src.replace("..", ""));
+    Ok(OnePieceCard {
+        name: card_name,
+        code: code.to_string(),
+        img_src,
+        rarity,
+        get_info: get_info.to_string(),
+        r#type: card_type,
+        set_name: set_name.to_string(),
+        last_fetched_at: LastFetchedAt::default(),
+    })
+}
+
+impl CardSource for OnePieceScraper {
+    // the card list page of a series already carries every field we keep
+    type Expansion = String;
+    type CardRef = OnePieceCard;
+    type Card = OnePieceCard;
+
+    fn game(&self) -> Game {
+        Game::OnePiece
+    }
+    async fn expansions(&self) -> Result<Vec<String>, Error> {
+        self.set().await
+    }
+    fn expansion_key(&self, series: &String) -> String {
+        series.clone()
+    }
+    async fn card_refs(
+        &self,
+        series: &String,
+    ) -> Result<Vec<Result<OnePieceCard, CardFailure>>, Error> {
+        self.scrape_cards(series).await
+    }
+    async fn card_detail(&self, card: OnePieceCard) -> Result<OnePieceCard, Error> {
+        Ok(card)
+    }
+    // cards have no page of their own, the image is the closest thing to one
+    fn card_url(&self, card: &OnePieceCard) -> String {
+        card.img_src.clone()
+    }
+    fn image_url(&self, card: &OnePieceCard) -> Option<url::Url> {
+        url::Url::parse(&card.img_src).ok()
+    }
+}
+
 #[derive(Debug)]
 pub struct OnePieceCard {
     pub name: String,
@@ -0,0 +1,106 @@
+use scraper::{ElementRef, Html, Selector};
+
+use super::scraper_error::ScraperError;
+
+// A parsed document that remembers where it came from, so a failed lookup
+// reports the url, the field we wanted and the selector that missed.
+pub(crate) struct Page {
+    url: String,
+    document: Html,
+}
+
+impl Page {
+    pub fn new(url: &str, source: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            document: Html::parse_document(source),
+        }
+    }
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+    pub fn root(&self) -> ElementRef<'_> {
+        self.document.root_element()
+    }
+    pub fn find<'a>(
+        &self,
+        scope: ElementRef<'a>,
+        field: &'static str,
+        selector: &str,
+    ) -> Result<ElementRef<'a>, ScraperError> {
+        self.find_opt(scope, selector)?
+            .ok_or_else(|| ScraperError::MissingElement {
+                url: self.url.clone(),
+                field,
+                selector: selector.to_string(),
+            })
+    }
+    pub fn find_opt<'a>(
+        &self,
+        scope: ElementRef<'a>,
+        selector: &str,
+    ) -> Result<Option<ElementRef<'a>>, ScraperError> {
+        Ok(scope.select(&parse_selector(selector)?).next())
+    }
+    pub fn select<'a>(
+        &self,
+        scope: ElementRef<'a>,
+        selector: &str,
+    ) -> Result<Vec<ElementRef<'a>>, ScraperError> {
+        Ok(scope.select(&parse_selector(selector)?).collect())
+    }
+    pub fn attr<'a>(
+        &self,
+        elem: ElementRef<'a>,
+        field: &'static str,
+        attr: &'static str,
+    ) -> Result<&'a str, ScraperError> {
+        elem.value()
+            .attr(attr)
+            .ok_or_else(|| ScraperError::MissingAttribute {
+                url: self.url.clone(),
+                field,
+                attr,
+            })
+    }
+    pub fn invalid(&self, field: &'static str, value: &str) -> ScraperError {
+        ScraperError::InvalidValue {
+            url: self.url.clone(),
+            field,
+            value: value.to_string(),
+        }
+    }
+}
+
+pub(crate) fn parse_selector(selector: &str) -> Result<Selector, ScraperError> {
+    Selector::parse(selector).map_err(|e| ScraperError::Selector(format!("{selector}: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn missing_element_carries_context() {
+        let page = Page::new(
+            "https://example.com/card/1",
+            "<div class='name'>Pikachu</div>",
+        );
+        let name = page.find(page.root(), "name", ".name").unwrap();
+        assert_eq!(name.inner_html(), "Pikachu");
+        let err = page.find(page.root(), "hp", ".hp").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "hp not found at https://example.com/card/1 (selector `.hp`)"
+        );
+    }
+    #[test]
+    fn missing_attribute_carries_context() {
+        let page = Page::new("https://example.com/card/1", "<img class='card'>");
+        let img = page.find(page.root(), "img_src", "img.card").unwrap();
+        let err = page.attr(img, "img_src", "src").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "img_src has no `src` attribute at https://example.com/card/1"
+        );
+    }
+}
@@ -5,11 +5,13 @@ use scraper::Selector;
 use crate::{
     domain::PtcgRarity,
     error::Error,
-    scraper::{get_source, Inner},
+    scraper::{client::HttpClient, Inner},
 };
 
 #[derive(Clone)]
-pub struct PokemonWikiScraper {}
+pub struct PokemonWikiScraper {
+    client: HttpClient,
+}
 
 #[derive(Debug)]
 pub struct PokemonWikiCard {
@@ -20,15 +22,15 @@ pub struct PokemonWikiCard {
 }
 
 impl PokemonWikiScraper {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(client: HttpClient) -> Self {
+        Self { client }
     }
     pub async fn fetch_card_data_by_exp_url(
         &self,
         exp_url: &str,
         exp_code: &str,
     ) -> Result<Vec<PokemonWikiCard>, Error> {
-        let source = get_source(exp_url).await?;
+        let source = self.client.get_source(exp_url).await?;
         let document = scraper::Html::parse_document(&source);
         let tr_selector =
             &Selector::parse("table > tbody > tr:nth-child(2) > td > table > tbody > tr").unwrap();
@@ -1,10 +1,15 @@
-use crate::{domain::PtcgRarity, error::Error};
+use crate::{
+    domain::{Game, PtcgRarity, PtcgRegion, PtcgSkillKind},
+    error::{CardFailure, Error},
+};
 use chrono::NaiveDate;
 use derive_builder::Builder;
 use html_escape::decode_html_entities;
 use scraper::{ElementRef, Selector};
+use std::fmt;
+use tracing::warn;
 
-use super::{get_source, scraper_error::ScraperError};
+use super::{card_source::CardSource, client::HttpClient, page::Page, scraper_error::ScraperError};
 
 const POKEMON_TRAINER_SITE_URL_BASE: &str = "https://asia.pokemon-card.com";
 
@@ -14,11 +19,19 @@ pub struct PtcgExpansion {
     pub series: String,
     pub name: String,
     pub release_date: NaiveDate,
+    pub region: PtcgRegion,
+}
+
+#[derive(Debug)]
+pub struct PtcgCardRef {
+    pub code: String,
+    pub expansion_code: String,
 }
 
 #[derive(Debug, Builder)]
 pub struct ThePTCGCard {
     pub code: String,
+    pub region: PtcgRegion,
     pub kind: String,
     pub evolve_marker: Option<String>,
     pub name: String,
@@ -30,22 +43,76 @@ pub struct ThePTCGCard {
     pub expansion_symbol: Option<String>,
     pub energy: Option<String>,
     pub number: Option<String>,
+    pub regulation_mark: Option<String>,
     pub artist: String,
     pub set_code: Option<String>,
+    #[builder(default)]
+    pub skills: Vec<PtcgSkill>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PtcgSkill {
+    pub kind: PtcgSkillKind,
+    pub name: Option<String>,
+    // energy types, one entry per symbol
+    pub cost: Vec<String>,
+    pub damage: Option<String>,
+    pub effect: Option<String>,
+}
+
+impl fmt::Display for PtcgSkill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}]", self.kind)?;
+        if let Some(name) = &self.name {
+            write!(f, " {name}")?;
+        }
+        if !self.cost.is_empty() {
+            write!(f, " {{{}}}", self.cost.join(","))?;
+        }
+        if let Some(damage) = &self.damage {
+            write!(f, " {damage}")?;
+        }
+        if let Some(effect) = &self.effect {
+            write!(f, ": {effect}")?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone)]
-pub struct PtcgScraper {}
+pub struct PtcgScraper {
+    client: HttpClient,
+    region: PtcgRegion,
+}
 
 impl PtcgScraper {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(client: HttpClient, region: PtcgRegion) -> Self {
+        Self { client, region }
+    }
+    pub fn region(&self) -> PtcgRegion {
+        self.region
+    }
+    // example: https://asia.pokemon-card.com/tw/card-search
+    fn site_url(&self) -> String {
+        format!("{POKEMON_TRAINER_SITE_URL_BASE}/{}", self.region.path())
+    }
+    pub fn detail_url(&self, code: &str) -> String {
+        format!("{}/card-search/detail/{code}/", self.site_url())
+    }
+    pub fn card_image_url(&self, code: &str) -> Option<url::Url> {
+        let code: i32 = code.parse().ok()?;
+        let region = self.region.path();
+        url::Url::parse(&format!(
+            "{}/card-img/{region}{code:08}.png",
+            self.site_url()
+        ))
+        .ok()
     }
     pub async fn fetch_expansion(&self) -> Result<Vec<PtcgExpansion>, Error> {
-        let mut site_url = format!("{}/tw/card-search", POKEMON_TRAINER_SITE_URL_BASE);
+        let mut site_url = format!("{}/card-search", self.site_url());
         let mut exps = vec![];
         loop {
-            let source = get_source(&site_url).await?;
+            let source = self.client.get_source(&site_url).await?;
             let document = scraper::Html::parse_document(&source);
             let expansion_link_selector = &Selector::parse(".expansionLink")
                 .map_err(|e| ScraperError::ScraperBackend(e.to_string()))?;
@@ -78,6 +145,7 @@ impl PtcgScraper {
                     series: series_elem.inner_html().trim().to_owned(),
                     name: decoded_name.to_string(),
                     release_date,
+                    region: self.region,
                 };
                 exps.push(pset);
             }
@@ -94,11 +162,13 @@ impl PtcgScraper {
         Ok(exps)
     }
     pub async fn get_fetchables_by_exp(&self, exp_code: &str) -> Result<Vec<String>, Error> {
-        let mut exp_url =
-            format!("https://asia.pokemon-card.com/tw/card-search/list/?expansionCodes={exp_code}");
+        let mut exp_url = format!(
+            "{}/card-search/list/?expansionCodes={exp_code}",
+            self.site_url()
+        );
         let mut card_codes = vec![];
         loop {
-            let source = get_source(&exp_url).await?;
+            let source = self.client.get_source(&exp_url).await?;
             let document = scraper::Html::parse_document(&source);
             let card_selector = &Selector::parse(".card a")
                 .map_err(|e| ScraperError::ScraperBackend(e.to_string()))?;
@@ -123,65 +193,86 @@ impl PtcgScraper {
         Ok(card_codes)
     }
     pub async fn fetch_printing_detail(&self, card_url: &str) -> Result<ThePTCGCard, Error> {
-        let source = get_source(card_url).await?;
+        let source = self.client.get_source(card_url).await?;
+        let page = Page::new(card_url, &source);
+        let root = page.root();
         let mut card_builder = ThePTCGCardBuilder::default();
-        let document = scraper::Html::parse_document(&source);
-        let common_header =
-            get_first_elem_inner_html(".commonHeader", document.root_element()).unwrap();
-        if common_header == "招式" {
-            card_builder.kind("寶可夢卡".to_string());
-        } else {
-            card_builder.kind(common_header);
-        }
-        let page_header_selector = Selector::parse(".pageHeader.cardDetail").unwrap();
-        let mut page_header = document
-            .select(&page_header_selector)
-            .next()
-            .unwrap()
-            .text();
+        let mut missing_fields = vec![];
+        let mut page_header = page.find(root, "name", ".pageHeader.cardDetail")?.text();
 
         // skip first empty string
         page_header.next();
         let evolve_marker = page_header.next().map(|s| s.trim().to_string());
         card_builder.evolve_marker(evolve_marker);
-        let name = page_header.next().unwrap().trim();
+        let name = page_header
+            .next()
+            .ok_or_else(|| page.invalid("name", ""))?
+            .trim();
         card_builder.name(name.to_string());
-        let img_selector = Selector::parse(".cardImage img").unwrap();
-        let img_elem = document.select(&img_selector).next().unwrap();
-        let img_src = img_elem.value().attr("src").unwrap();
-        card_builder.img_src(Some(img_src.to_string()));
-        let hp =
-            get_first_elem_inner_html(".cardInformationColumn .number", document.root_element());
+        let img_src = page
+            .find_opt(root, ".cardImage img")?
+            .and_then(|img| img.value().attr("src"));
+        if img_src.is_none() {
+            missing_fields.push("img_src");
+        }
+        card_builder.img_src(img_src.map(|s| s.to_string()));
+        let hp = first_inner_html(&page, ".cardInformationColumn .number")?;
+        // headers are in the language of the region, only Pokémon have HP in every one of them
+        let pokemon = hp.is_some();
+        match first_inner_html(&page, ".commonHeader")? {
+            _ if pokemon => {
+                card_builder.kind("寶可夢卡".to_string());
+            }
+            Some(common_header) => {
+                card_builder.kind(common_header);
+            }
+            None => {
+                missing_fields.push("kind");
+                card_builder.kind(String::new());
+            }
+        }
         card_builder.hp(hp);
 
-        let energy_selector = Selector::parse(".mainInfomation img").unwrap();
-        let energy = document
-            .select(&energy_selector)
-            .next()
-            .map(|s| s.value().attr("src").unwrap().to_owned());
+        let energy = page
+            .find_opt(root, ".mainInfomation img")?
+            .and_then(|img| img.value().attr("src"))
+            .map(|s| s.to_owned());
         card_builder.energy(energy);
 
-        let weak_point = get_first_elem_inner_html(".weakpoint", document.root_element());
+        let weak_point = first_inner_html(&page, ".weakpoint")?;
         card_builder.weak_point(weak_point);
-        let resist = get_first_elem_inner_html(".resist", document.root_element());
+        let resist = first_inner_html(&page, ".resist")?;
         card_builder.resist(resist);
-        let escape = get_first_elem_inner_html(".escape", document.root_element());
+        let escape = first_inner_html(&page, ".escape")?;
         card_builder.escape(escape);
-        let expansion_symbol =
-            get_first_elem_inner_html(".expansionSymbol", document.root_element());
+        let expansion_symbol = first_inner_html(&page, ".expansionSymbol")?;
         card_builder.expansion_symbol(expansion_symbol);
-        let collector_number =
-            get_first_elem_inner_html(".collectorNumber", document.root_element());
+        let collector_number = first_inner_html(&page, ".collectorNumber")?;
+        if collector_number.is_none() {
+            missing_fields.push("number");
+        }
         card_builder.number(collector_number);
-        let artist = get_first_elem_inner_html(".illustrator a", document.root_element())
-            .unwrap_or_default();
-        card_builder.artist(artist);
-        let mut card_url = card_url.to_string();
-        card_url.pop();
-        let (_, code) = card_url.rsplit_once('/').unwrap();
+        // the card search can filter by regulation, but only the detail page shows the mark
+        let regulation_mark = first_inner_html(&page, ".expansionColumn .alpha")?;
+        card_builder.regulation_mark(regulation_mark.filter(|mark| !mark.is_empty()));
+        let artist = first_inner_html(&page, ".illustrator a")?;
+        if artist.is_none() {
+            missing_fields.push("artist");
+        }
+        card_builder.artist(artist.unwrap_or_default());
+        let code = card_url
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .map(|(_, code)| code)
+            .ok_or_else(|| page.invalid("code", card_url))?;
         card_builder.code(code.to_string());
+        card_builder.region(self.region);
         card_builder.set_code(None);
-        let card = card_builder.build().unwrap();
+        card_builder.skills(parse_skills(&page, pokemon)?);
+        if !missing_fields.is_empty() {
+            warn!("{card_url}: partial card, missing {missing_fields:?}");
+        }
+        let card = card_builder.build().map_err(ScraperError::from)?;
         Ok(card)
     }
     pub async fn rarity_ids(&self, rarity: &PtcgRarity) -> Result<Vec<String>, Error> {
@@ -209,8 +300,8 @@ impl PtcgScraper {
         let mut ids = vec![];
         let mut page_num = 1;
         loop {
-            let url = format!("https://asia.pokemon-card.com/tw/card-search/list/?pageNo={}&sortCondition=&keyword=&cardType=all&regulation=all&pokemonEnergy=&pokemonWeakness=&pokemonResistance=&pokemonMoveEnergy=&hpLowerLimit=none&hpUpperLimit=none&retreatCostLowerLimit=0&retreatCostUpperLimit=none&rarity%5B0%5D={}&illustratorName=&expansionCodes=", page_num, rarity_label_number);
-            let source = get_source(&url).await?;
+            let url = format!("{}/card-search/list/?pageNo={}&sortCondition=&keyword=&cardType=all&regulation=all&pokemonEnergy=&pokemonWeakness=&pokemonResistance=&pokemonMoveEnergy=&hpLowerLimit=none&hpUpperLimit=none&retreatCostLowerLimit=0&retreatCostUpperLimit=none&rarity%5B0%5D={}&illustratorName=&expansionCodes=", self.site_url(), page_num, rarity_label_number);
+            let source = self.client.get_source(&url).await?;
             let document = scraper::Html::parse_document(&source);
             let selector = &Selector::parse("#noResult").unwrap();
             let selection = document.select(selector);
@@ -233,9 +324,239 @@ impl PtcgScraper {
     }
 }
 
-fn get_first_elem_inner_html(s: &str, elem: ElementRef) -> Option<String> {
-    let selector = &Selector::parse(s).unwrap();
-    elem.select(selector)
-        .next()
-        .map(|s| s.inner_html().trim().to_owned())
+impl CardSource for PtcgScraper {
+    type Expansion = PtcgExpansion;
+    type CardRef = PtcgCardRef;
+    type Card = ThePTCGCard;
+
+    fn game(&self) -> Game {
+        Game::Ptcg
+    }
+    async fn expansions(&self) -> Result<Vec<PtcgExpansion>, Error> {
+        self.fetch_expansion().await
+    }
+    fn expansion_key(&self, expansion: &PtcgExpansion) -> String {
+        expansion.code.clone()
+    }
+    async fn card_refs(
+        &self,
+        expansion: &PtcgExpansion,
+    ) -> Result<Vec<Result<PtcgCardRef, CardFailure>>, Error> {
+        let codes = self.get_fetchables_by_exp(&expansion.code).await?;
+        Ok(codes
+            .into_iter()
+            .map(|code| {
+                Ok(PtcgCardRef {
+                    code,
+                    expansion_code: expansion.code.clone(),
+                })
+            })
+            .collect())
+    }
+    async fn card_detail(&self, card_ref: PtcgCardRef) -> Result<ThePTCGCard, Error> {
+        let mut card = self
+            .fetch_printing_detail(&self.detail_url(&card_ref.code))
+            .await?;
+        card.set_code = Some(card_ref.expansion_code);
+        Ok(card)
+    }
+    fn card_url(&self, card_ref: &PtcgCardRef) -> String {
+        self.detail_url(&card_ref.code)
+    }
+    // the detail page does not name its expansion code
+    fn card_identifier(&self, card_ref: &PtcgCardRef) -> Option<String> {
+        Some(format!("{}|{}", card_ref.expansion_code, card_ref.code))
+    }
+    fn image_url(&self, card: &ThePTCGCard) -> Option<url::Url> {
+        self.card_image_url(&card.code)
+    }
+}
+
+// every .skill of every text box section, in page order. Section headers are in the
+// language of the region, so the kind comes from the markup: attacks have a cost or damage,
+// abilities only a name, rule boxes and trainer or energy effects no name at all
+fn parse_skills(page: &Page, pokemon: bool) -> Result<Vec<PtcgSkill>, ScraperError> {
+    let mut skills = vec![];
+    for section in page.select(page.root(), ".skillInformation")? {
+        let section_skills = page.select(section, ".skill")?;
+        let mut attacks = false;
+        for skill in &section_skills {
+            attacks |= page.find_opt(*skill, ".skillCost img")?.is_some()
+                || page
+                    .find_opt(*skill, ".skillDamage")?
+                    .is_some_and(|d| !text(d).is_empty());
+        }
+        for skill in section_skills {
+            let mut name = page
+                .find_opt(skill, ".skillName")?
+                .map(text)
+                .filter(|n| !n.is_empty());
+            let kind = match name.as_deref() {
+                None if pokemon => PtcgSkillKind::Rule,
+                None => PtcgSkillKind::Effect,
+                // abilities are sometimes listed among the attacks with a prefix, e.g. [特性]
+                Some(n) if n.starts_with('[') && n.contains(']') => {
+                    name = n.split_once(']').map(|(_, n)| n.trim().to_string());
+                    PtcgSkillKind::Ability
+                }
+                Some(_) if !pokemon => PtcgSkillKind::Effect,
+                Some(_) if attacks => PtcgSkillKind::Attack,
+                Some(_) => PtcgSkillKind::Ability,
+            };
+            let cost = page
+                .select(skill, ".skillCost img")?
+                .into_iter()
+                .filter_map(|img| img.value().attr("src").and_then(energy_type))
+                .collect();
+            skills.push(PtcgSkill {
+                kind,
+                name,
+                cost,
+                damage: page
+                    .find_opt(skill, ".skillDamage")?
+                    .map(text)
+                    .filter(|d| !d.is_empty()),
+                effect: page
+                    .find_opt(skill, ".skillEffect")?
+                    .map(text)
+                    .filter(|e| !e.is_empty()),
+            });
+        }
+    }
+    Ok(skills)
+}
+
+// example: https://asia.pokemon-card.com/various_images/energy/Grass.png
+fn energy_type(src: &str) -> Option<String> {
+    let file = src.rsplit('/').next()?;
+    let stem = file.split_once('.').map_or(file, |(stem, _)| stem);
+    (!stem.is_empty()).then(|| stem.to_string())
+}
+
+fn text(elem: ElementRef) -> String {
+    elem.text()
+        .map(str::trim)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string()
+}
+
+fn first_inner_html(page: &Page, selector: &str) -> Result<Option<String>, ScraperError> {
+    Ok(page
+        .find_opt(page.root(), selector)?
+        .map(|s| s.inner_html().trim().to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_skills() {
+        let source = r#"
+        <div class="skillInformation">
+            <h3 class="commonHeader">特性</h3>
+            <div class="skill">
+                <h4><span class="skillName">電氣引擎</span></h4>
+                <p class="skillEffect">在自己的回合可以使用1次。</p>
+            </div>
+        </div>
+        <div class="skillInformation">
+            <h3 class="commonHeader">招式</h3>
+            <div class="skill">
+                <h4>
+                    <span class="skillCost">
+                        <img src="https://asia.pokemon-card.com/various_images/energy/Lightning.png" class="energy">
+                        <img src="https://asia.pokemon-card.com/various_images/energy/Colorless.png" class="energy">
+                    </span>
+                    <span class="skillName">十萬伏特</span>
+                    <span class="skillDamage">90</span>
+                </h4>
+                <p class="skillEffect"></p>
+            </div>
+        </div>
+        <div class="skillInformation">
+            <h3 class="commonHeader">特殊規則</h3>
+            <div class="skill"><p class="skillEffect">寶可夢ex【昏厥】時，對手獲得2張獎賞卡。</p></div>
+        </div>
+        "#;
+        let page = Page::new(
+            "https://asia.pokemon-card.com/tw/card-search/detail/1/",
+            source,
+        );
+        let skills = parse_skills(&page, true).unwrap();
+        assert_eq!(skills.len(), 3);
+        assert_eq!(skills[0].kind, PtcgSkillKind::Ability);
+        assert_eq!(skills[0].name.as_deref(), Some("電氣引擎"));
+        assert_eq!(
+            skills[1],
+            PtcgSkill {
+                kind: PtcgSkillKind::Attack,
+                name: Some("十萬伏特".to_string()),
+                cost: vec!["Lightning".to_string(), "Colorless".to_string()],
+                damage: Some("90".to_string()),
+                effect: None,
+            }
+        );
+        assert_eq!(skills[2].kind, PtcgSkillKind::Rule);
+        assert_eq!(skills[2].name, None);
+    }
+
+    #[test]
+    fn test_parse_skills_english() {
+        let source = r#"
+        <div class="skillInformation">
+            <h3 class="commonHeader">Ability</h3>
+            <div class="skill">
+                <h4><span class="skillName">Electric Engine</span></h4>
+                <p class="skillEffect">Once during your turn, you may use this Ability.</p>
+            </div>
+        </div>
+        <div class="skillInformation">
+            <h3 class="commonHeader">Attack</h3>
+            <div class="skill">
+                <h4>
+                    <span class="skillCost">
+                        <img src="https://asia.pokemon-card.com/various_images/energy/Lightning.png" class="energy">
+                    </span>
+                    <span class="skillName">Thunderbolt</span>
+                    <span class="skillDamage">90</span>
+                </h4>
+            </div>
+            <div class="skill">
+                <h4><span class="skillName">[Ability] Static Charge</span></h4>
+                <p class="skillEffect">Once during your turn, you may attach a Basic Energy.</p>
+            </div>
+        </div>
+        <div class="skillInformation">
+            <h3 class="commonHeader">Pokémon ex rule</h3>
+            <div class="skill"><p class="skillEffect">When your Pokémon ex is Knocked Out, your opponent takes 2 Prize cards.</p></div>
+        </div>
+        "#;
+        let page = Page::new(
+            "https://asia.pokemon-card.com/sg/card-search/detail/1/",
+            source,
+        );
+        let skills = parse_skills(&page, true).unwrap();
+        let kinds: Vec<_> = skills.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                PtcgSkillKind::Ability,
+                PtcgSkillKind::Attack,
+                PtcgSkillKind::Ability,
+                PtcgSkillKind::Rule
+            ]
+        );
+        assert_eq!(skills[2].name.as_deref(), Some("Static Charge"));
+
+        let trainer = Page::new(
+            "https://asia.pokemon-card.com/sg/card-search/detail/2/",
+            r#"<div class="skillInformation"><h3 class="commonHeader">Supporter</h3>
+            <div class="skill"><p class="skillEffect">Draw 3 cards.</p></div></div>"#,
+        );
+        let skills = parse_skills(&trainer, false).unwrap();
+        assert_eq!(skills[0].kind, PtcgSkillKind::Effect);
+    }
 }
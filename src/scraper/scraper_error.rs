@@ -17,4 +17,28 @@ pub enum ScraperError {
     Cmd(#[from] CmdError),
     #[error("reqwest error {0}")]
     Reqwest(#[from] reqwest::Error),
+    #[error("page not archived {0}")]
+    NotArchived(String),
+    #[error("network access in replay mode {0}")]
+    ReplayNetwork(String),
+    #[error("invalid selector `{0}`")]
+    Selector(String),
+    #[error("{field} not found at {url} (selector `{selector}`)")]
+    MissingElement {
+        url: String,
+        field: &'static str,
+        selector: String,
+    },
+    #[error("{field} has no `{attr}` attribute at {url}")]
+    MissingAttribute {
+        url: String,
+        field: &'static str,
+        attr: &'static str,
+    },
+    #[error("{field} could not be parsed from {value:?} at {url}")]
+    InvalidValue {
+        url: String,
+        field: &'static str,
+        value: String,
+    },
 }
@@ -1,36 +1,41 @@
 use chrono::NaiveDate;
 use scraper::Selector;
 
-use crate::{domain::PtcgRarity, error::Error};
+use crate::{
+    domain::{Game, PtcgRarity},
+    error::{CardFailure, Error},
+};
 
-use super::{get_source, Inner};
+use super::{card_source::CardSource, client::HttpClient, page::Page, Inner};
 
-pub struct TcgCollectorScraper {}
+pub struct TcgCollectorScraper {
+    pub client: HttpClient,
+}
 
 impl TcgCollectorScraper {
     pub async fn fetch_exps(&self) -> Result<Vec<PtcgJpExpansion>, Error> {
         let url = "https://www.tcgcollector.com/expansions/jp?collectionProgressMode=anyCardVariant&releaseDateOrder=newToOld&displayAs=logos";
-        let source = get_source(url).await?;
-        let document = scraper::Html::parse_document(&source);
+        let source = self.client.get_source(url).await?;
+        let page = Page::new(url, &source);
 
-        // example: Mar 22, 2024
-        let grid_items_sel = Selector::parse(".expansion-logo-grid-item").unwrap();
-        let grid_items = document.select(&grid_items_sel);
         let mut exps = vec![];
-        for item in grid_items {
-            let sel = &Selector::parse(".expansion-logo-grid-item-release-date").unwrap();
-            let mut selected_release_date = item.select(sel);
-            let release_date = selected_release_date.next().unwrap().inner_trim();
-            let d = chrono::NaiveDate::parse_from_str(&release_date, "%b %d, %Y").unwrap();
-
-            let name_sel = &Selector::parse(".expansion-logo-grid-item-expansion-name").unwrap();
-            let name_el = item.select(name_sel).next().unwrap();
+        for item in page.select(page.root(), ".expansion-logo-grid-item")? {
+            // example: Mar 22, 2024
+            let release_date = page
+                .find(
+                    item,
+                    "release_date",
+                    ".expansion-logo-grid-item-release-date",
+                )?
+                .inner_trim();
+            let d = chrono::NaiveDate::parse_from_str(&release_date, "%b %d, %Y")
+                .map_err(|_| page.invalid("release_date", &release_date))?;
+
+            let name_el = page.find(item, "name", ".expansion-logo-grid-item-expansion-name")?;
             let name = name_el.inner_trim();
 
-            let code_sel = &Selector::parse(".expansion-logo-grid-item-expansion-code").unwrap();
-            let maybe_code = item
-                .select(code_sel)
-                .next()
+            let maybe_code = page
+                .find_opt(item, ".expansion-logo-grid-item-expansion-code")?
                 .map(|e| e.inner_lowercase_trim());
 
             let Some(code) = maybe_code else {
@@ -40,17 +45,15 @@ impl TcgCollectorScraper {
                 .attr("href")
                 .map(|s| format!("https://www.tcgcollector.com{}", s));
 
-            let symbol_sel = &Selector::parse(".expansion-symbol").unwrap();
-            let symbol_src = item
-                .select(symbol_sel)
-                .next()
-                .map(|el| el.attr("src").unwrap().to_string());
+            let symbol_src = page
+                .find_opt(item, ".expansion-symbol")?
+                .and_then(|el| el.attr("src"))
+                .map(|s| s.to_string());
 
-            let logo_sel = &Selector::parse(".expansion-logo-grid-item-expansion-logo").unwrap();
-            let logo_src = item
-                .select(logo_sel)
-                .next()
-                .map(|el| el.attr("src").unwrap().to_string());
+            let logo_src = page
+                .find_opt(item, ".expansion-logo-grid-item-expansion-logo")?
+                .and_then(|el| el.attr("src"))
+                .map(|s| s.to_string());
 
             let exp = PtcgJpExpansion {
                 name,
@@ -69,8 +72,15 @@ impl TcgCollectorScraper {
         &self,
         link: &str,
     ) -> Result<Vec<TcgCollectorCardDetail>, Error> {
+        let mut cards: Vec<TcgCollectorCardDetail> = vec![];
+        for card_ref in self.fetch_card_refs(link).await? {
+            cards.push(self.fetch_card_html(card_ref).await?);
+        }
+        Ok(cards)
+    }
+    pub async fn fetch_card_refs(&self, link: &str) -> Result<Vec<TcgCollectorCardRef>, Error> {
         let url = format!("{}?displayAs=list", link);
-        let source = get_source(&url).await?;
+        let source = self.client.get_source(&url).await?;
         let document = scraper::Html::parse_document(&source);
 
         let exp_code_sel = &Selector::parse("#card-search-result-title-expansion-code").unwrap();
@@ -83,12 +93,10 @@ impl TcgCollectorScraper {
         let card_items_sel = &Selector::parse(".card-list-item").unwrap();
         let card_items = document.select(card_items_sel);
 
-        let mut cards: Vec<TcgCollectorCardDetail> = vec![];
+        let mut cards: Vec<TcgCollectorCardRef> = vec![];
         for item in card_items {
             let name_sel = &Selector::parse(".card-list-item-entry-text").unwrap();
             let url_path = item.select(name_sel).next().unwrap().attr("href").unwrap();
-            let url = format!("https://www.tcgcollector.com{}", url_path);
-            let html = get_source(&url).await?;
 
             let name = item.select(name_sel).next().unwrap().inner_trim();
 
@@ -103,12 +111,11 @@ impl TcgCollectorScraper {
                 .unwrap_or_default();
             let rarity: PtcgRarity = TcgCollectorRarity(rarity).into();
 
-            let card = TcgCollectorCardDetail {
+            let card = TcgCollectorCardRef {
                 name,
                 exp_code: exp_code.clone(),
                 number,
-                html,
-                rarity: Some(rarity),
+                rarity,
                 url: format!("https://www.tcgcollector.com{}", url_path),
             };
             cards.push(card);
@@ -116,12 +123,26 @@ impl TcgCollectorScraper {
 
         Ok(cards)
     }
+    pub async fn fetch_card_html(
+        &self,
+        card_ref: TcgCollectorCardRef,
+    ) -> Result<TcgCollectorCardDetail, Error> {
+        let html = self.client.get_source(&card_ref.url).await?;
+        Ok(TcgCollectorCardDetail {
+            name: card_ref.name,
+            number: card_ref.number,
+            exp_code: card_ref.exp_code,
+            html,
+            url: card_ref.url,
+            rarity: Some(card_ref.rarity),
+        })
+    }
     pub async fn fetch_card_rarity(
         &self,
         link: &str,
     ) -> Result<Vec<TcgCollectorCardRarity>, Error> {
         let url = format!("{}?displayAs=list", link);
-        let source = get_source(&url).await?;
+        let source = self.client.get_source(&url).await?;
         let document = scraper::Html::parse_document(&source);
 
         let card_items_sel = &Selector::parse(".card-list-item").unwrap();
@@ -186,7 +207,62 @@ impl TcgCollectorScraper {
     }
 }
 
-#[derive(Debug)]
+impl CardSource for TcgCollectorScraper {
+    type Expansion = PtcgJpExpansion;
+    type CardRef = TcgCollectorCardRef;
+    type Card = TcgCollectorCardDetail;
+
+    fn game(&self) -> Game {
+        Game::PtcgJp
+    }
+    async fn expansions(&self) -> Result<Vec<PtcgJpExpansion>, Error> {
+        self.fetch_exps().await
+    }
+    fn expansion_key(&self, exp: &PtcgJpExpansion) -> String {
+        exp.code.clone()
+    }
+    async fn card_refs(
+        &self,
+        exp: &PtcgJpExpansion,
+    ) -> Result<Vec<Result<TcgCollectorCardRef, CardFailure>>, Error> {
+        let Some(link) = &exp.link else {
+            return Ok(vec![]);
+        };
+        let card_refs = self.fetch_card_refs(link).await?;
+        Ok(card_refs.into_iter().map(Ok).collect())
+    }
+    async fn card_detail(
+        &self,
+        card_ref: TcgCollectorCardRef,
+    ) -> Result<TcgCollectorCardDetail, Error> {
+        self.fetch_card_html(card_ref).await
+    }
+    fn card_url(&self, card_ref: &TcgCollectorCardRef) -> String {
+        card_ref.url.clone()
+    }
+    // exp_code|number|name, read back by `TcgCollectorCardRef::from_identifier`
+    fn card_identifier(&self, card_ref: &TcgCollectorCardRef) -> Option<String> {
+        Some(format!(
+            "{}|{}|{}",
+            card_ref.exp_code, card_ref.number, card_ref.name
+        ))
+    }
+    fn image_url(&self, card: &TcgCollectorCardDetail) -> Option<url::Url> {
+        card_image_url(&card.html)
+    }
+}
+
+pub fn card_image_url(html: &str) -> Option<url::Url> {
+    let document = scraper::Html::parse_document(html);
+    let image_sel = &Selector::parse("#card-image-container img").unwrap();
+    let src = document.select(image_sel).next()?.attr("src")?;
+    url::Url::parse("https://www.tcgcollector.com")
+        .ok()?
+        .join(src)
+        .ok()
+}
+
+#[derive(Debug, Clone)]
 pub struct PtcgJpExpansion {
     pub name: String,
     pub code: String,
@@ -202,6 +278,28 @@ pub struct TcgCollectorCardRarity {
     pub url: String,
 }
 
+#[derive(Debug, Clone)]
+pub struct TcgCollectorCardRef {
+    pub name: String,
+    pub number: String,
+    pub exp_code: String,
+    pub url: String,
+    pub rarity: PtcgRarity,
+}
+
+impl TcgCollectorCardRef {
+    pub fn from_identifier(url: &str, identifier: &str) -> Option<Self> {
+        let mut parts = identifier.splitn(3, '|');
+        Some(Self {
+            exp_code: parts.next()?.to_string(),
+            number: parts.next()?.to_string(),
+            name: parts.next()?.to_string(),
+            url: url.to_string(),
+            rarity: PtcgRarity::Unknown,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TcgCollectorCardDetail {
     pub name: String,
@@ -220,6 +318,8 @@ pub struct PtcgJpCard {
     pub desc: Option<String>,
     pub skill1_name_en: Option<String>,
     pub skill1_damage: Option<String>,
+    // not saved by `ptcg-jp card`, kept for the dbg! output
+    #[allow(dead_code)]
     pub rarity: Option<PtcgRarity>,
 }
 
@@ -252,3 +352,20 @@ impl<'a> From<TcgCollectorRarity<'a>> for PtcgRarity {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_card_image_url() {
+        let html = r#"<div id="card-image-container">
+            <img src="https://static.tcgcollector.com/content/images/ab/cd/ef.jpg" alt="Pikachu">
+        </div>"#;
+        assert_eq!(
+            card_image_url(html).unwrap().as_str(),
+            "https://static.tcgcollector.com/content/images/ab/cd/ef.jpg"
+        );
+        assert!(card_image_url("<div></div>").is_none());
+    }
+}
@@ -0,0 +1,145 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use fantoccini::{wd::Capabilities, Client, ClientBuilder, Locator};
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+
+use super::{env_parse, yugioh::Error};
+
+#[derive(Debug, Clone)]
+pub struct WebDriverConfig {
+    pub url: String,
+    // sessions open at the same time
+    pub pool_size: usize,
+    // a session is closed and replaced after this many pages
+    pub max_pages: u32,
+    pub wait_timeout: Duration,
+}
+
+impl Default for WebDriverConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:4444".to_string(),
+            pool_size: 1,
+            max_pages: 200,
+            wait_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+impl WebDriverConfig {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(url) = std::env::var("WEBDRIVER_URL") {
+            config.url = url;
+        }
+        if let Some(size) = env_parse("WEBDRIVER_POOL_SIZE") {
+            config.pool_size = size;
+        }
+        if let Some(pages) = env_parse("WEBDRIVER_MAX_PAGES") {
+            config.max_pages = pages;
+        }
+        if let Some(secs) = env_parse("WEBDRIVER_WAIT_SECS") {
+            config.wait_timeout = Duration::from_secs(secs);
+        }
+        config
+    }
+}
+
+struct Session {
+    client: Client,
+    pages: u32,
+}
+
+impl Session {
+    async fn source(
+        &mut self,
+        url: &str,
+        wait_for: &str,
+        timeout: Duration,
+    ) -> Result<String, Error> {
+        self.pages += 1;
+        self.client.goto(url).await?;
+        self.client
+            .wait()
+            .at_most(timeout)
+            .for_element(Locator::Css(wait_for))
+            .await?;
+        Ok(self.client.source().await?)
+    }
+    async fn is_healthy(&self) -> bool {
+        self.client.current_url().await.is_ok()
+    }
+    async fn close(self) {
+        if let Err(err) = self.client.close().await {
+            warn!("could not close webdriver session: {err}");
+        }
+    }
+}
+
+// Sessions are opened lazily, so nothing connects to the driver until a page is needed
+#[derive(Clone)]
+pub struct SessionPool {
+    config: Arc<WebDriverConfig>,
+    cap: Capabilities,
+    permits: Arc<Semaphore>,
+    idle: Arc<Mutex<Vec<Session>>>,
+}
+
+impl SessionPool {
+    pub fn new(config: WebDriverConfig) -> Self {
+        let mut cap = Capabilities::new();
+        cap.insert(
+            "moz:firefoxOptions".to_string(),
+            serde_json::json!({"args": ["--headless"]}),
+        );
+        Self {
+            permits: Arc::new(Semaphore::new(config.pool_size.max(1))),
+            config: Arc::new(config),
+            cap,
+            idle: Arc::default(),
+        }
+    }
+    pub async fn source(&self, url: &str, wait_for: &str) -> Result<String, Error> {
+        let _permit = self.permits.acquire().await.expect("pool semaphore closed");
+        let mut session = self.checkout().await?;
+        let result = session
+            .source(url, wait_for, self.config.wait_timeout)
+            .await;
+        // a failed page may leave the browser in any state, start over with a fresh one
+        if result.is_err() || session.pages >= self.config.max_pages {
+            session.close().await;
+        } else {
+            self.idle.lock().unwrap().push(session);
+        }
+        result
+    }
+    async fn checkout(&self) -> Result<Session, Error> {
+        loop {
+            let session = self.idle.lock().unwrap().pop();
+            let Some(session) = session else {
+                break;
+            };
+            if session.is_healthy().await {
+                return Ok(session);
+            }
+            warn!("dropping unhealthy webdriver session");
+            session.close().await;
+        }
+        info!("opening webdriver session on {}", self.config.url);
+        let client = ClientBuilder::native()
+            .capabilities(self.cap.clone())
+            .connect(&self.config.url)
+            .await?;
+        Ok(Session { client, pages: 0 })
+    }
+    pub async fn close(&self) {
+        let sessions = std::mem::take(&mut *self.idle.lock().unwrap());
+        for session in sessions {
+            session.close().await;
+        }
+    }
+}
@@ -1,19 +1,18 @@
-use scraper::Selector;
+use scraper::{ElementRef, Selector};
 
+use super::{card_source::CardSource, client::HttpClient, page::Page};
 use crate::{
-    domain::LastFetchedAt,
-    error::{Error, ErrorCode},
+    domain::{Game, LastFetchedAt},
+    error::{CardFailure, Error, ErrorCode},
 };
 
-pub(crate) struct WsScraper {}
+pub(crate) struct WsScraper {
+    pub client: HttpClient,
+}
 impl WsScraper {
-    pub async fn get_source(url: &str) -> Result<String, Error> {
-        Ok(reqwest::Client::new().get(url).send().await?.text().await?)
-    }
-
     pub async fn get_total_pages(&self) -> Result<i32, Error> {
         let url = "https://ws-tcg.com/cardlist/search";
-        let source = Self::get_source(url).await?;
+        let source = self.client.get_source(url).await?;
         let document = scraper::Html::parse_document(&source);
         let selector =
             Selector::parse("#searchResults > p:nth-child(4) > span:nth-child(12) > a").unwrap();
@@ -30,62 +29,96 @@ impl WsScraper {
     pub(crate) async fn scrape_by_page(
         &self,
         page_no: i32,
-    ) -> Result<Vec<Result<WsCard, ErrorCode>>, Error> {
-        let url = format!("https://ws-tcg.com/cardlist/search?page={}", page_no);
-        let source = Self::get_source(&url).await?;
-        let document = scraper::Html::parse_document(&source);
-        let selector = Selector::parse("table.search-result-table tbody tr").unwrap();
-        let trs = document.select(&selector);
-        let mut results = vec![];
-        for tr in trs {
-            let selector = &Selector::parse("h4").unwrap();
-            let set_name = tr
-                .select(selector)
-                .next()
-                .ok_or(Error::FieldMissing("set_name not found".to_string()))?
-                .text()
-                .last()
-                .ok_or(Error::FieldMissing("set_name not found".to_string()))?;
-            let selector = &Selector::parse("a span").unwrap();
-            let mut spans = tr.select(selector);
-            let card_name = spans
-                .next()
-                .ok_or(Error::FieldMissing("card_name not found".to_string()))?;
-            let card_no = spans.next().unwrap().inner_html();
-            let a = card_no.clone();
-            let (set_code, _) = a.split_once('/').unwrap();
-            let selector = &Selector::parse("img").unwrap();
-            let img_src = tr
-                .select(selector)
-                .next()
-                .ok_or(Error::FieldMissing("img_src not found".to_string()))?
-                .value()
-                .attr("src")
-                .ok_or(Error::FieldMissing("src not found".to_string()))?;
-            let selector = &Selector::parse("td span").unwrap();
-            let mut rarity: Option<String> = None;
-            for span in tr.select(selector) {
-                if span.inner_html().contains("レアリティ") {
-                    rarity = Some(span.inner_html().replace("レアリティ：", ""));
-                    break;
-                }
-            }
-            let last_fetched_at = LastFetchedAt::default();
-            let card = WsCard {
-                name: card_name.inner_html().trim().to_string(),
-                code: card_no,
-                set_code: set_code.to_string(),
-                img_src: img_src.to_owned(),
-                rarity,
-                set_name: set_name.to_string().replacen('-', "", 1),
-                last_fetched_at,
-            };
-            results.push(Ok(card))
-        }
+    ) -> Result<Vec<Result<WsCard, CardFailure>>, Error> {
+        let url = page_url(page_no);
+        let source = self.client.get_source(&url).await?;
+        let page = Page::new(&url, &source);
+        let results = page
+            .select(page.root(), "table.search-result-table tbody tr")?
+            .into_iter()
+            .map(|tr| {
+                parse_row(&page, tr).map_err(|code| {
+                    let card_no = page
+                        .select(tr, "a span")
+                        .ok()
+                        .and_then(|spans| spans.get(1).map(|s| s.inner_html()));
+                    CardFailure::new(&url, card_no, code, Some(tr.html()))
+                })
+            })
+            .collect();
         Ok(results)
     }
 }
 
+pub(crate) fn page_url(page_no: i32) -> String {
+    format!("https://ws-tcg.com/cardlist/search?page={}", page_no)
+}
+
+fn parse_row(page: &Page, tr: ElementRef) -> Result<WsCard, ErrorCode> {
+    let set_name = page
+        .find(tr, "set_name", "h4")?
+        .text()
+        .last()
+        .ok_or_else(|| page.invalid("set_name", ""))?;
+    let spans = page.select(tr, "a span")?;
+    let card_name = spans.first().ok_or_else(|| page.invalid("card_name", ""))?;
+    let card_no = spans
+        .get(1)
+        .ok_or_else(|| page.invalid("card_no", ""))?
+        .inner_html();
+    let (set_code, _) = card_no
+        .split_once('/')
+        .ok_or_else(|| page.invalid("set_code", &card_no))?;
+    let img_src = page.attr(page.find(tr, "img_src", "img")?, "img_src", "src")?;
+    let mut rarity: Option<String> = None;
+    for span in page.select(tr, "td span")? {
+        if span.inner_html().contains("レアリティ") {
+            rarity = Some(span.inner_html().replace("レアリティ：", ""));
+            break;
+        }
+    }
+    let last_fetched_at = LastFetchedAt::default();
+    Ok(WsCard {
+        name: card_name.inner_html().trim().to_string(),
+        code: card_no.clone(),
+        set_code: set_code.to_string(),
+        img_src: img_src.to_owned(),
+        rarity,
+        set_name: set_name.to_string().replacen('-', "", 1),
+        last_fetched_at,
+    })
+}
+
+impl CardSource for WsScraper {
+    // WS has no expansion listing, every search result page stands in for one
+    type Expansion = i32;
+    type CardRef = WsCard;
+    type Card = WsCard;
+
+    fn game(&self) -> Game {
+        Game::Ws
+    }
+    async fn expansions(&self) -> Result<Vec<i32>, Error> {
+        let total_pages = self.get_total_pages().await?;
+        Ok((1..=total_pages).collect())
+    }
+    fn expansion_key(&self, page_no: &i32) -> String {
+        page_no.to_string()
+    }
+    async fn card_refs(&self, page_no: &i32) -> Result<Vec<Result<WsCard, CardFailure>>, Error> {
+        self.scrape_by_page(*page_no).await
+    }
+    async fn card_detail(&self, card: WsCard) -> Result<WsCard, Error> {
+        Ok(card)
+    }
+    fn card_url(&self, card: &WsCard) -> String {
+        format!("https://ws-tcg.com/cardlist/?cardno={}", card.code)
+    }
+    fn image_url(&self, card: &WsCard) -> Option<url::Url> {
+        url::Url::parse(&format!("https://ws-tcg.com{}", card.img_src)).ok()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct WsCard {
     pub name: String,
@@ -1,10 +1,24 @@
 use derive_builder::Builder;
-use fantoccini::{wd::Capabilities, ClientBuilder, Locator};
-use scraper::Selector;
+use lazy_static::lazy_static;
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
 
+use tracing::{info, warn};
+
+use super::{
+    card_source::CardSource, client::HttpClient, page::Page, scraper_error::ScraperError,
+    webdriver::SessionPool,
+};
+use crate::{
+    domain::{Game, YugiohLegality, YugiohLocale, YugiohRarity, YugiohRemark},
+    error::CardFailure,
+};
+
+#[derive(Clone)]
 pub(crate) struct YugiohScraper {
-    cap: Capabilities,
-    url: String,
+    sessions: SessionPool,
+    client: HttpClient,
+    pub locale: YugiohLocale,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -13,43 +27,66 @@ pub enum Error {
     NewSession(#[from] fantoccini::error::NewSessionError),
     #[error("could not connect to website")]
     Cmd(#[from] fantoccini::error::CmdError),
-    #[error("scraper error")]
-    Scraper(#[from] scraper::error::SelectorErrorKind<'static>),
+    #[error("scraper error {0}")]
+    Scraper(String),
     #[error("attribute not found {0}")]
     AttrNotfound(String),
     #[error("card id is not exists")]
     CardIdNotExists,
+    #[error("parse error {0}")]
+    Parse(#[from] ScraperError),
+    #[error("http client error {0}")]
+    Client(#[from] Box<crate::error::Error>),
+}
+
+// SelectorErrorKind is not Send, keep only its message
+impl From<scraper::error::SelectorErrorKind<'static>> for Error {
+    fn from(value: scraper::error::SelectorErrorKind<'static>) -> Self {
+        Error::Scraper(value.to_string())
+    }
 }
 
 impl YugiohScraper {
-    pub(crate) fn new() -> Self {
-        let mut cap = Capabilities::new();
-        cap.insert(
-            "moz:firefoxOptions".to_string(),
-            serde_json::json!({"args": ["--headless"]}),
-        );
+    pub(crate) fn new(client: HttpClient, sessions: SessionPool, locale: YugiohLocale) -> Self {
         Self {
-            cap,
-            url: "http://localhost:4444".to_string(),
+            sessions,
+            client,
+            locale,
         }
     }
+    pub(crate) fn db_url(&self, link: &str) -> String {
+        format!(
+            "https://www.db.yugioh-card.com{link}&request_locale={}",
+            self.locale
+        )
+    }
+    // plain http first, the browser only for pages whose markup is rendered by javascript
+    async fn page_source(&self, url: &str, wait_for: &'static str) -> Result<String, Error> {
+        if self.client.is_replay() {
+            return Ok(self.client.replayed(url).await.map_err(Box::new)?);
+        }
+        let resp = self.client.get(url).await.map_err(Box::new)?;
+        let source = resp
+            .text()
+            .await
+            .map_err(|err| Box::new(crate::error::Error::from(err)))?;
+        let source = if is_rendered(&source, wait_for)? {
+            source
+        } else {
+            info!("{url} has no `{wait_for}` without javascript, falling back to webdriver");
+            self.sessions.source(url, wait_for).await?
+        };
+        self.client.archive(url, &source).await.map_err(Box::new)?;
+        Ok(source)
+    }
     pub async fn fetch_expansion_link(&self) -> Result<Vec<String>, Error> {
-        let client = ClientBuilder::native()
-            .capabilities(self.cap.clone())
-            .connect(&self.url)
-            .await?;
-        client
-            .goto(
-                "https://www.db.yugioh-card.com/yugiohdb/card_list.action?clm=1&request_locale=ja",
-            )
-            .await?;
-        client
-            .wait()
-            .for_element(Locator::Css("#card_list_1 .card_list #list_title_1"))
+        let url = self.db_url("/yugiohdb/card_list.action?clm=1");
+        let source = self
+            .page_source(&url, "#card_list_1 .card_list #list_title_1")
             .await?;
-        let source = client.source().await?;
         let document = scraper::Html::parse_document(&source);
-        let selector = &Selector::parse("#card_list_1 .card_list .pack_ja .link_value")?;
+        let selector = format!("#card_list_1 .card_list .pack_{} .link_value", self.locale);
+        let selector = &Selector::parse(&selector).map_err(|e| Error::Scraper(e.to_string()))?;
         let mut links = vec![];
         for elem in document.select(selector) {
             let v = elem
@@ -61,16 +98,7 @@ impl YugiohScraper {
         Ok(links)
     }
     pub async fn fetch_printing_link(&self, expansion_link: &str) -> Result<Vec<String>, Error> {
-        let client = ClientBuilder::native()
-            .capabilities(self.cap.clone())
-            .connect(&self.url)
-            .await?;
-        client.goto(expansion_link).await?;
-        client
-            .wait()
-            .for_element(Locator::Css("#card_list"))
-            .await?;
-        let source = client.source().await?;
+        let source = self.page_source(expansion_link, "#card_list").await?;
         let document = scraper::Html::parse_document(&source);
         let selector = &Selector::parse("#card_list .t_row.c_normal .link_value")?;
         let mut links = vec![];
@@ -83,91 +111,464 @@ impl YugiohScraper {
         }
         Ok(links)
     }
-    pub async fn fetch_printing_detail(&self, link: &str) -> Result<Vec<YugiohPrinting>, Error> {
+    // the OCG list, read from the Japanese database whatever the scraper locale
+    pub async fn fetch_forbidden_limited(&self) -> Result<ForbiddenLimitedList, Error> {
+        let url =
+            "https://www.db.yugioh-card.com/yugiohdb/forbidden_limited.action?request_locale=ja";
+        let source = self.page_source(url, "#list_forbidden").await?;
+        Ok(parse_forbidden_limited(&Page::new(url, &source))?)
+    }
+    pub async fn fetch_printing_detail(&self, link: &str) -> Result<YugiohCardDetail, Error> {
         let mut builder = YugiohPrintingBuilder::create_empty();
         let (_, query) = link.split_once('?').ok_or_else(|| Error::CardIdNotExists)?;
         for qs in query.split('&') {
-            let (key, value) = qs.split_once('=').unwrap();
-            if key == "cid" {
+            if let Some(("cid", value)) = qs.split_once('=') {
                 builder.card_id(value.to_owned());
             }
         }
         if builder.card_id.is_none() {
             return Err(Error::CardIdNotExists);
         }
-        let client = ClientBuilder::native()
-            .capabilities(self.cap.clone())
-            .connect(&self.url)
-            .await?;
-        client.goto(link).await?;
-        client
-            .wait()
-            .for_element(Locator::Css("#article_body"))
-            .await?;
-        let source = client.source().await?;
-        let document = scraper::Html::parse_document(&source);
-        let selector = &Selector::parse("#article_body #cardname")?;
-        for elem in document.select(selector) {
+        let source = self.page_source(link, "#article_body").await?;
+        let page = Page::new(link, &source);
+        builder.locale(self.locale.to_string());
+        builder.name_jp(String::new());
+        builder.name_en(String::new());
+        for elem in page.select(page.root(), "#article_body #cardname")? {
+            if self.locale != YugiohLocale::Ja {
+                builder.name(texts(elem).first().unwrap_or(&"").to_string());
+                continue;
+            }
             for (i, t) in elem.text().enumerate() {
                 if i == 3 {
                     builder.name_jp(t.trim().to_string());
+                    builder.name(t.trim().to_string());
                 }
                 if i == 4 {
                     builder.name_en(t.to_string());
                 }
             }
         }
+        // the attribute labels are only parsed in Japanese, other locales link to the ja card by card_id
+        let card = if self.locale == YugiohLocale::Ja {
+            let mut card = YugiohCard {
+                card_id: builder.card_id.clone().unwrap_or_default(),
+                name_jp: builder.name_jp.clone().unwrap_or_default(),
+                name_en: builder.name_en.clone().unwrap_or_default(),
+                ..Default::default()
+            };
+            parse_card_attributes(&page, &mut card)?;
+            card.img_src = image_url(&source);
+            Some(card)
+        } else {
+            None
+        };
+        let last_inner = |elem, selector| -> Result<Option<String>, ScraperError> {
+            Ok(page
+                .select(elem, selector)?
+                .last()
+                .map(|f| f.inner_html().trim().to_owned()))
+        };
         let mut printings = vec![];
-        for elem in document.select(&Selector::parse("#update_list .t_body .t_row")?) {
+        for elem in page.select(page.root(), "#update_list .t_body .t_row")? {
             let mut b = builder.clone();
-            let release_date = elem
-                .select(&Selector::parse(".time").unwrap())
-                .last()
-                .map(|f| f.inner_html().trim().to_owned())
-                .unwrap();
+            // like the ptcg detail page: required fields fail the card, optional ones are logged
+            let mut missing_fields = vec![];
+            let release_date =
+                last_inner(elem, ".time")?.ok_or_else(|| page.invalid("release_date", ""))?;
             b.release_date(release_date);
-            let number = elem
-                .select(&Selector::parse(".card_number").unwrap())
-                .last()
-                .map(|f| f.inner_html().trim().to_owned())
-                .unwrap();
-            let (r#ref, number) = number.split_once('-').unwrap_or(("NONE", "000"));
-            b.r#ref(r#ref.to_owned());
-            b.number(format!("{}-{number}", r#ref));
-            let expansion_name = elem
-                .select(&Selector::parse(".pack_name").unwrap())
-                .last()
-                .map(|f| f.inner_html().trim().to_owned())
-                .unwrap();
+            let number = last_inner(elem, ".card_number")?;
+            if number.is_none() {
+                missing_fields.push("number");
+            }
+            let (r#ref, number) = split_card_number(number);
+            b.r#ref(r#ref);
+            b.number(number);
+            let expansion_name = last_inner(elem, ".pack_name")?
+                .ok_or_else(|| page.invalid("expansion_name", ""))?;
             b.expansion_name(expansion_name);
-            let rarity = elem
-                .select(&Selector::parse(".icon p").unwrap())
-                .last()
-                .map(|f| f.inner_html().trim().to_owned())
-                .unwrap();
-            b.rarity(rarity);
-            let remark = elem
-                .select(&Selector::parse(".icon span").unwrap())
-                .last()
-                .map(|f| f.inner_html().trim().to_owned())
-                .unwrap();
-            b.remark(remark);
-            let printing = b.build().unwrap();
+            let rarity = last_inner(elem, ".icon p")?.ok_or_else(|| page.invalid("rarity", ""))?;
+            b.rarity(YugiohRarity::from_code(&rarity));
+            let remark = last_inner(elem, ".icon span")?;
+            if remark.is_none() {
+                missing_fields.push("remark");
+            }
+            b.remark(remark.unwrap_or_default());
+            if !missing_fields.is_empty() {
+                warn!("{link}: partial printing, missing {missing_fields:?}");
+            }
+            let printing = b
+                .build()
+                .map_err(|e| page.invalid("printing", &e.to_string()))?;
             printings.push(printing)
         }
-        Ok(printings)
+        Ok(YugiohCardDetail { card, printings })
+    }
+}
+
+fn parse_forbidden_limited(page: &Page) -> Result<ForbiddenLimitedList, ScraperError> {
+    lazy_static! {
+        static ref DATE: Regex = Regex::new(r"(\d{4})年(\d{1,2})月(\d{1,2})日").unwrap();
+        static ref CID: Regex = Regex::new(r"cid=(\d+)").unwrap();
+    }
+    let body = page.find(page.root(), "effective_date", "#article_body")?;
+    let text = texts(body).concat();
+    let effective_date = DATE
+        .captures(&text)
+        .and_then(|c| {
+            chrono::NaiveDate::from_ymd_opt(
+                c[1].parse().ok()?,
+                c[2].parse().ok()?,
+                c[3].parse().ok()?,
+            )
+        })
+        .ok_or_else(|| page.invalid("effective_date", ""))?;
+    let mut cards = vec![];
+    for (selector, status) in [
+        (
+            "#list_forbidden .t_row .link_value",
+            YugiohLegality::Forbidden,
+        ),
+        ("#list_limited .t_row .link_value", YugiohLegality::Limited),
+        (
+            "#list_semi_limited .t_row .link_value",
+            YugiohLegality::SemiLimited,
+        ),
+    ] {
+        for link in page.select(body, selector)? {
+            let link = page.attr(link, "card_id", "value")?;
+            let card_id = CID
+                .captures(link)
+                .ok_or_else(|| page.invalid("card_id", link))?;
+            cards.push((card_id[1].to_string(), status));
+        }
+    }
+    // saving an empty list would make every card Unlimited, a layout change is far more likely
+    if cards.is_empty() {
+        return Err(ScraperError::MissingElement {
+            url: page.url().to_string(),
+            field: "cards",
+            selector: ".t_row .link_value".to_string(),
+        });
+    }
+    Ok(ForbiddenLimitedList {
+        effective_date,
+        cards,
+    })
+}
+
+// LB-05 is card 05 of LB; promotional printings may have no number at all
+fn split_card_number(number: Option<String>) -> (Option<String>, Option<String>) {
+    let Some(number) = number.filter(|n| !n.is_empty()) else {
+        return (None, None);
+    };
+    let r#ref = number.split_once('-').map(|(r#ref, _)| r#ref.to_string());
+    (r#ref, Some(number))
+}
+
+// the labelled boxes of the detail page, e.g. 属性 / 闇属性, レベル / レベル 8, 攻撃力 / 3000
+fn parse_card_attributes(page: &Page, card: &mut YugiohCard) -> Result<(), ScraperError> {
+    for item in page.select(page.root(), "#article_body .item_box")? {
+        let (Some(title), Some(value)) = (
+            page.find_opt(item, ".item_box_title")?,
+            page.find_opt(item, ".item_box_value")?,
+        ) else {
+            continue;
+        };
+        let value = texts(value).join(" ");
+        match texts(title).join("").as_str() {
+            "属性" => card.attribute = Some(value.trim_end_matches("属性").to_string()),
+            "レベル" => card.level = first_number(&value),
+            "ランク" => card.rank = first_number(&value),
+            "リンク" => {
+                card.link_rating = first_number(&value);
+                card.link_arrows = link_arrows(page, item)?;
+            }
+            "攻撃力" => card.atk = Some(value),
+            "守備力" => card.def = Some(value),
+            "ペンデュラムスケール" => card.pendulum_scale = first_number(&value),
+            // spell and trap icon, e.g. 速攻
+            "効果" => card.types.push(value),
+            _ => {}
+        }
+    }
+    if let Some(species) = page.find_opt(page.root(), "#article_body .species")? {
+        let species = texts(species).join("");
+        card.types.extend(
+            species
+                .split('／')
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(String::from),
+        );
+    }
+    for text in page.select(page.root(), "#article_body .item_box_text")? {
+        let title = match page.find_opt(text, ".text_title")? {
+            Some(title) => texts(title),
+            None => vec![],
+        };
+        let body = texts(text)[title.len()..].join("\n");
+        if title.concat().contains("ペンデュラム") {
+            card.pendulum_effect = Some(body);
+        } else {
+            card.effect_text = Some(body);
+        }
+    }
+    Ok(())
+}
+
+// the card image is loaded by script, its url (with the enc token it needs) only appears in the page source
+fn image_url(source: &str) -> Option<String> {
+    lazy_static! {
+        static ref RE: Regex =
+            Regex::new(r#"/yugiohdb/get_image\.action\?[^"'\s]*cid=\d+[^"'\s]*"#).unwrap();
     }
+    let path = html_escape::decode_html_entities(RE.find(source)?.as_str()).to_string();
+    Some(format!("https://www.db.yugioh-card.com{path}"))
+}
+
+fn texts(elem: ElementRef<'_>) -> Vec<&str> {
+    elem.text()
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect()
+}
+
+fn first_number(value: &str) -> Option<i32> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"\d+").unwrap();
+    }
+    RE.find(value)?.as_str().parse().ok()
+}
+
+// link marker images are named after numpad positions, e.g. link7.png or link139.png
+fn link_arrows(page: &Page, item: ElementRef<'_>) -> Result<Vec<String>, ScraperError> {
+    lazy_static! {
+        static ref RE: Regex = Regex::new(r"link(\d+)\.png").unwrap();
+    }
+    let mut arrows = vec![];
+    for img in page.select(item, "img")? {
+        let src = page.attr(img, "link_arrows", "src")?;
+        let Some(digits) = RE.captures(src).and_then(|c| c.get(1)) else {
+            continue;
+        };
+        for digit in digits.as_str().chars() {
+            let arrow = match digit {
+                '1' => "bottom-left",
+                '2' => "bottom",
+                '3' => "bottom-right",
+                '4' => "left",
+                '6' => "right",
+                '7' => "top-left",
+                '8' => "top",
+                '9' => "top-right",
+                _ => continue,
+            };
+            arrows.push(arrow.to_string());
+        }
+    }
+    Ok(arrows)
+}
+
+impl CardSource for YugiohScraper {
+    // expansion and card references are card_list.action / card_search.action paths
+    type Expansion = String;
+    type CardRef = String;
+    type Card = YugiohCardDetail;
+
+    fn game(&self) -> Game {
+        Game::Yugioh
+    }
+    async fn expansions(&self) -> Result<Vec<String>, crate::error::Error> {
+        Ok(self.fetch_expansion_link().await?)
+    }
+    fn expansion_key(&self, link: &String) -> String {
+        link.clone()
+    }
+    async fn card_refs(
+        &self,
+        link: &String,
+    ) -> Result<Vec<Result<String, CardFailure>>, crate::error::Error> {
+        let links = self.fetch_printing_link(&self.db_url(link)).await?;
+        Ok(links.into_iter().map(Ok).collect())
+    }
+    async fn card_detail(&self, link: String) -> Result<YugiohCardDetail, crate::error::Error> {
+        Ok(self.fetch_printing_detail(&self.db_url(&link)).await?)
+    }
+    fn card_url(&self, link: &String) -> String {
+        self.db_url(link)
+    }
+    // only cards read from the ja database carry one
+    fn image_url(&self, detail: &YugiohCardDetail) -> Option<url::Url> {
+        let img_src = detail.card.as_ref()?.img_src.as_deref()?;
+        url::Url::parse(img_src).ok()
+    }
+    // get_image.action names nothing, same path as `yugioh download-images`
+    fn image_name(&self, detail: &YugiohCardDetail, _image_url: &url::Url) -> String {
+        let card_id = detail
+            .card
+            .as_ref()
+            .map_or("", |card| card.card_id.as_str());
+        format!("yugioh/{card_id}.jpg")
+    }
+}
+
+fn is_rendered(source: &str, wait_for: &'static str) -> Result<bool, Error> {
+    let selector = Selector::parse(wait_for)?;
+    let document = Html::parse_document(source);
+    let rendered = document.select(&selector).next().is_some();
+    Ok(rendered)
+}
+
+#[derive(Debug)]
+pub struct ForbiddenLimitedList {
+    pub effective_date: chrono::NaiveDate,
+    pub cards: Vec<(String, YugiohLegality)>,
+}
+
+#[derive(Debug)]
+pub struct YugiohCardDetail {
+    pub card: Option<YugiohCard>,
+    pub printings: Vec<YugiohPrinting>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct YugiohCard {
+    pub card_id: String,
+    pub name_jp: String,
+    pub name_en: String,
+    pub attribute: Option<String>,
+    pub level: Option<i32>,
+    pub rank: Option<i32>,
+    pub link_rating: Option<i32>,
+    pub types: Vec<String>,
+    pub atk: Option<String>,
+    pub def: Option<String>,
+    pub pendulum_scale: Option<i32>,
+    pub pendulum_effect: Option<String>,
+    pub link_arrows: Vec<String>,
+    pub effect_text: Option<String>,
+    pub img_src: Option<String>,
 }
 
 #[derive(Builder, Debug)]
 pub struct YugiohPrinting {
     pub card_id: String,
+    pub locale: String,
+    // card name as printed in the locale
+    pub name: String,
     pub name_jp: String,
     pub name_en: String,
-    pub rarity: String,
-    pub number: String,
+    pub rarity: YugiohRarity,
+    pub number: Option<String>,
     pub release_date: String,
     pub remark: String,
     pub expansion_name: String,
-    pub r#ref: String,
+    pub r#ref: Option<String>,
+    // stored copy of the card image, see `Yugioh::download_images`
+    #[builder(default)]
+    pub image: Option<String>,
+    // of the card, on the Forbidden/Limited list in effect
+    #[builder(default)]
+    pub legality: YugiohLegality,
+}
+
+impl YugiohPrinting {
+    pub fn remark_flags(&self) -> Vec<YugiohRemark> {
+        YugiohRemark::parse(&self.remark)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_rendered() {
+        let server_rendered = r#"<div id="card_list"><div class="t_row c_normal"></div></div>"#;
+        let script_only = r#"<div id="app"></div><script src="card_list.js"></script>"#;
+        assert!(is_rendered(server_rendered, "#card_list").unwrap());
+        assert!(!is_rendered(script_only, "#card_list").unwrap());
+    }
+    #[test]
+    fn test_split_card_number() {
+        assert_eq!(
+            split_card_number(Some("LB-05".to_string())),
+            (Some("LB".to_string()), Some("LB-05".to_string()))
+        );
+        assert_eq!(split_card_number(Some(String::new())), (None, None));
+        assert_eq!(split_card_number(None), (None, None));
+    }
+    #[test]
+    fn test_rarity_codes() {
+        assert_eq!(YugiohRarity::from_code("20SE"), YugiohRarity::TwentiethSE);
+        assert_eq!(YugiohRarity::from_code("QCSE").to_string(), "QCSE");
+        let unknown = YugiohRarity::from_code("OF");
+        assert_eq!(unknown, YugiohRarity::Unknown("OF".to_string()));
+        assert_eq!(unknown.to_string(), "OF");
+    }
+    #[test]
+    fn test_parse_forbidden_limited() {
+        let source = r#"
+        <div id="article_body">
+          <p>2026年10月1日 適用</p>
+          <div id="list_forbidden"><div class="t_row">
+            <input class="link_value" value="/yugiohdb/card_search.action?ope=2&cid=4861"></div></div>
+          <div id="list_limited"></div>
+          <div id="list_semi_limited"><div class="t_row">
+            <input class="link_value" value="/yugiohdb/card_search.action?ope=2&cid=7734"></div></div>
+        </div>
+        "#;
+        let list = parse_forbidden_limited(&Page::new("https://example.com", source)).unwrap();
+        assert_eq!(
+            list.effective_date,
+            chrono::NaiveDate::from_ymd_opt(2026, 10, 1).unwrap()
+        );
+        assert_eq!(
+            list.cards,
+            vec![
+                ("4861".to_string(), YugiohLegality::Forbidden),
+                ("7734".to_string(), YugiohLegality::SemiLimited),
+            ]
+        );
+        let empty = r#"<div id="article_body"><p>2026年10月1日 適用</p></div>"#;
+        assert!(parse_forbidden_limited(&Page::new("https://example.com", empty)).is_err());
+    }
+    #[test]
+    fn test_image_url() {
+        let source = r#"$('#card_image_1').attr('src', '/yugiohdb/get_image.action?type=1&amp;lang=ja&amp;cid=4007&amp;ciid=1&amp;enc=Qx2_Yr&amp;osplang=1');"#;
+        assert_eq!(
+            image_url(source).as_deref(),
+            Some("https://www.db.yugioh-card.com/yugiohdb/get_image.action?type=1&lang=ja&cid=4007&ciid=1&enc=Qx2_Yr&osplang=1")
+        );
+    }
+    #[test]
+    fn test_parse_card_attributes() {
+        let source = r#"
+        <div id="article_body">
+          <div class="item_box"><span class="item_box_title"><b>属性</b></span>
+            <span class="item_box_value">闇属性</span></div>
+          <div class="item_box"><span class="item_box_title"><b>リンク</b></span>
+            <span class="item_box_value">リンク 2 <img src="/parts/link_pc/link13.png"></span></div>
+          <div class="item_box"><span class="item_box_title"><b>攻撃力</b></span>
+            <span class="item_box_value">1500</span></div>
+          <p class="species"><span>サイバース族</span><span>／</span><span>リンク</span></p>
+          <div class="item_box_text"><div class="text_title">カードテキスト</div>
+            効果モンスター２体<br>このカード名の効果は１ターンに１度しか使用できない。</div>
+        </div>
+        "#;
+        let page = Page::new("https://example.com", source);
+        let mut card = YugiohCard::default();
+        parse_card_attributes(&page, &mut card).unwrap();
+        assert_eq!(card.attribute.as_deref(), Some("闇"));
+        assert_eq!(card.link_rating, Some(2));
+        assert_eq!(card.link_arrows, vec!["bottom-left", "bottom-right"]);
+        assert_eq!(card.atk.as_deref(), Some("1500"));
+        assert_eq!(card.def, None);
+        assert_eq!(card.types, vec!["サイバース族", "リンク"]);
+        assert_eq!(
+            card.effect_text.as_deref(),
+            Some("効果モンスター２体\nこのカード名の効果は１ターンに１度しか使用できない。")
+        );
+    }
 }
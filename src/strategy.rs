@@ -1,7 +1,38 @@
-use std::{collections::HashMap, ops::Range};
+use std::{collections::HashMap, fmt, ops::Range};
 
 use serde::Deserialize;
 
+const WIKI_HOSTS: &[&str] = &["wiki.52poke.com"];
+
+#[derive(thiserror::Error, Debug)]
+pub enum StrategyError {
+    #[error("invalid strategy json: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0} is not a known wiki host")]
+    UnknownWikiHost(url::Url),
+    #[error("range [{}, {}] is empty", .0.start, .0.end)]
+    EmptyRange(Range<i32>),
+    #[error("mapping \"{0}\": \"{1}\" is not number: exp|number")]
+    InvalidMapping(String, String),
+    #[error("expansion {0} is not in pokemon_trainer_expansion")]
+    UnknownExpansion(String),
+    #[error("no stored strategy for {0}")]
+    NotStored(String),
+}
+
+// sources of `from` missing in `to` as "- ", the sources of `to` as "+ " when new, "  " otherwise
+pub fn diff_sources(from: &[serde_json::Value], to: &[serde_json::Value]) -> Vec<String> {
+    let removed = from
+        .iter()
+        .filter(|source| !to.contains(source))
+        .map(|source| format!("- {source}"));
+    let kept = to.iter().map(|source| match from.contains(source) {
+        true => format!("  {source}"),
+        false => format!("+ {source}"),
+    });
+    removed.chain(kept).collect()
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(tag = "source", rename_all = "snake_case")]
 pub enum Source {
@@ -11,6 +42,86 @@ pub enum Source {
     TcgCollector(TcgCollectorStrategy),
 }
 
+impl Source {
+    // checks that need no network or database
+    pub fn validate(&self) -> Vec<StrategyError> {
+        let mut errors = vec![];
+        match self {
+            Source::Wiki(WikiStrategy::Data(data)) => {
+                if !data
+                    .url
+                    .host_str()
+                    .is_some_and(|host| WIKI_HOSTS.contains(&host))
+                {
+                    errors.push(StrategyError::UnknownWikiHost(data.url()));
+                }
+                if let Some(range) = data.range().filter(|r| r.start > r.end) {
+                    errors.push(StrategyError::EmptyRange(range));
+                }
+            }
+            Source::TcgCollector(TcgCollectorStrategy::Pic(data))
+                if data.range.start > data.range.end =>
+            {
+                errors.push(StrategyError::EmptyRange(data.range()));
+            }
+            Source::TcgCollector(TcgCollectorStrategy::PicMappings(data)) => {
+                let mut mappings: Vec<_> = data.mappings.iter().collect();
+                mappings.sort();
+                errors.extend(
+                    mappings
+                        .into_iter()
+                        .filter_map(|(number, target)| parse_mapping(number, target).err()),
+                );
+            }
+            _ => {}
+        }
+        errors
+    }
+}
+
+// the planned action, as printed by `ptcg strategy --dry-run`
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Ptcg(PtcgStrategy::All) => {
+                write!(f, "ptcg all: fetch fetchables and printings")
+            }
+            Source::Ptcg(PtcgStrategy::Pic) => {
+                write!(f, "ptcg pic: download the image of every printing")
+            }
+            Source::Wiki(WikiStrategy::Data(data)) => {
+                write!(f, "wiki data: card names from {}", data.url)?;
+                match &data.range {
+                    Some(range) => write!(f, " for cards {}-{}", range.start, range.end),
+                    None => Ok(()),
+                }
+            }
+            Source::TcgCollector(TcgCollectorStrategy::Pic(data)) => write!(
+                f,
+                "tcg_collector pic: images of cards {}-{} from the same JP expansion",
+                data.range.start, data.range.end
+            ),
+            Source::TcgCollector(TcgCollectorStrategy::PicByName(data)) => write!(
+                f,
+                "tcg_collector pic_by_name: images matched by English name from {}",
+                data.exps.join(", ")
+            ),
+            Source::TcgCollector(TcgCollectorStrategy::PicMappings(data)) => {
+                let mut mappings: Vec<_> = data
+                    .mappings
+                    .iter()
+                    .map(|(number, target)| format!("{number} <- {target}"))
+                    .collect();
+                mappings.sort();
+                write!(f, "tcg_collector pic_mappings: {}", mappings.join(", "))
+            }
+            Source::Manual(ManualStrategy::Data(data)) => {
+                write!(f, "manual data: upsert {} cards", data.card_data.len())
+            }
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum ManualStrategy {
@@ -75,6 +186,61 @@ pub struct WikiData {
     range: Option<Range<i32>>,
 }
 
+impl Data {
+    pub fn card_data(&self) -> &[CardData] {
+        &self.card_data
+    }
+}
+
+impl CardData {
+    pub fn number(&self) -> &str {
+        &self.number
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl PicByName {
+    pub fn exps(&self) -> &[String] {
+        &self.exps
+    }
+}
+
+impl TcgCollectorPic {
+    pub fn range(&self) -> Range<i32> {
+        self.range.clone()
+    }
+}
+
+impl PicMappings {
+    // "185": "sm9|96" maps card 185 of the row's expansion to card 96 of sm9
+    pub fn mappings(&self) -> Result<Vec<(i32, &str, i32)>, StrategyError> {
+        self.mappings
+            .iter()
+            .map(|(number, target)| parse_mapping(number, target))
+            .collect()
+    }
+}
+
+fn parse_mapping<'a>(number: &str, target: &'a str) -> Result<(i32, &'a str, i32), StrategyError> {
+    let parsed = target.split_once('|').and_then(|(exp, jp_number)| {
+        let exp = Some(exp).filter(|exp| !exp.is_empty())?;
+        Some((card_number(number)?, exp, card_number(jp_number)?))
+    });
+    parsed.ok_or_else(|| StrategyError::InvalidMapping(number.to_string(), target.to_string()))
+}
+
+// leading number of "096/095", None for numbers like "SM-P"
+pub fn card_number(number: &str) -> Option<i32> {
+    number.split('/').next()?.trim().parse().ok()
+}
+
+// the ranges in our strategy CSVs include both ends
+pub fn in_range(range: &Range<i32>, number: &str) -> bool {
+    card_number(number).is_some_and(|n| range.start <= n && n <= range.end)
+}
+
 impl WikiData {
     pub fn url(&self) -> url::Url {
         self.url.clone()
@@ -142,6 +308,68 @@ mod tests {
         let _source: Source = serde_json::from_str(json).unwrap();
     }
     #[test]
+    fn test_card_number() {
+        assert_eq!(card_number("096/095"), Some(96));
+        assert_eq!(card_number("185"), Some(185));
+        assert_eq!(card_number("SM-P"), None);
+        assert!(in_range(&(71..91), "091/090"));
+        assert!(!in_range(&(71..91), "070/090"));
+        let json = r#"{ "mappings": { "185": "sm9|96", "186": "sm9b" } }"#;
+        let mappings: PicMappings = serde_json::from_str(json).unwrap();
+        assert!(matches!(
+            mappings.mappings(),
+            Err(StrategyError::InvalidMapping(number, _)) if number == "186"
+        ));
+        let json = r#"{ "mappings": { "185": "sm9|96" } }"#;
+        let mappings: PicMappings = serde_json::from_str(json).unwrap();
+        assert_eq!(mappings.mappings().unwrap(), vec![(185, "sm9", 96)]);
+    }
+    #[test]
+    fn test_validate() {
+        let json = r#"[
+  { "source": "wiki", "url": "https://example.com/wiki/x", "type": "data", "range": [91, 71] },
+  { "source": "tcg_collector", "type": "pic", "range": [71, 91] },
+  { "source": "tcg_collector", "type": "pic_mappings", "mappings": { "185": "sm9|96", "186": "sm9b" } }
+]"#;
+        let sources: Vec<Source> = serde_json::from_str(json).unwrap();
+        let errors: Vec<String> = sources
+            .iter()
+            .flat_map(Source::validate)
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(
+            errors,
+            vec![
+                "https://example.com/wiki/x is not a known wiki host",
+                "range [91, 71] is empty",
+                "mapping \"186\": \"sm9b\" is not number: exp|number",
+            ]
+        );
+        assert_eq!(
+            sources[2].to_string(),
+            "tcg_collector pic_mappings: 185 <- sm9|96, 186 <- sm9b"
+        );
+    }
+    #[test]
+    fn test_diff_sources() {
+        let from: Vec<serde_json::Value> = serde_json::from_str(
+            r#"[{"source": "ptcg", "type": "all"}, {"source": "ptcg", "type": "pic"}]"#,
+        )
+        .unwrap();
+        let to: Vec<serde_json::Value> = serde_json::from_str(
+            r#"[{"source": "ptcg", "type": "all"}, {"source": "tcg_collector", "type": "pic", "range": [1, 5]}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            diff_sources(&from, &to),
+            vec![
+                r#"- {"source":"ptcg","type":"pic"}"#,
+                r#"  {"source":"ptcg","type":"all"}"#,
+                r#"+ {"range":[1,5],"source":"tcg_collector","type":"pic"}"#,
+            ]
+        );
+    }
+    #[test]
     fn test_vec_sources1() {
         let json = r#"[
   {